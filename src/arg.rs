@@ -3,31 +3,81 @@ use crate::{
         OsStringToUtf8Error,
         MissingArgError,
         InvalidArgError,
+        SafeStateError,
+        MigrateError,
     },
     getter_setter::{
+        self,
+        schema,
         FullConfig,
         Config,
         RelayCount,
         RelayMax,
+        RelayName,
         MaxTime,
         GpioPin, 
+        Polarity,
+        Recovery,
+        Overlap,
         Timezone,
         Multiplier,
+        CalibrationCurve,
+        WarnAt,
+        Wear,
+        GpioBackend,
+        GpioChip,
+        GpioLine,
+        DoseUnit,
+        FlowRate,
+    },
+    unit::{
+        Amount,
+        Dose,
     },
     DIR_NAME,
+    calibrate,
     file,
+    forecast::{
+        self,
+        Forecast,
+    },
+    gpio,
     DynError,
     help::HELP,
+    reconcile::{
+        self,
+        Repair,
+    },
+    relay::{
+        self,
+        Retired,
+    },
+    wear::{
+        self,
+        WearPolicy,
+    },
 };
 
 use std::{
     env,
-    env::Args,
     fs,
+    io,
+    iter::Peekable,
+    vec,
 };
 
 use chrono_tz::Tz;
 
+use toml_edit::Datetime;
+
+#[cfg(test)]
+mod test;
+
+/// the command line after the command being matched. Commands 
+/// with an optional argument peek at the next one and leave it
+/// for the next command unless it is theirs.
+pub type Args = Peekable<vec::IntoIter<String>>;
+
 pub fn arg_matcher(
     arg: String,
     args: &mut Args
@@ -42,13 +92,61 @@ pub fn arg_matcher(
         "--show" => {
             show()?;
         }
+
+        "--maintenance-log" => {
+            maintenance_log()?;
+        }
+
+        "--forecast" => {
+            forecast()?;
+        }
         //////////////////////////
         // Execution
         "--exec" => {
             exec(args)?;   
         }
+
+        "--dispense" => {
+            dispense(args)?;
+        }
+
+        "--force-off" => {
+            force_off(args)?;
+        }
+
+        "--safe-state" => {
+            safe_state()?;
+        }
+
+        "--ack" => {
+            acknowledge(args)?;
+        }
+
+        "--reconcile" => {
+            reconcile(args)?;
+        }
         //////////////////////////
         // Configuration (in order)
+        "--init-profile" => {
+            init_profile(args)?;
+        }
+
+        "--migrate" => {
+            migrate()?;
+        }
+
+        "--add-relay" => {
+            add_relay(args)?;
+        }
+
+        "--set-relay" => {
+            set_relay(args)?;
+        }
+
+        "--replace-relay" => {
+            replace_relay(args)?;
+        }
+
         "--set-num-actuations" => {
             set_actuations(args)?; 
         }
@@ -57,6 +155,14 @@ pub fn arg_matcher(
             set_max_actuations(args)?;
         }
 
+        "--set-wear-warnings" => {
+            set_wear_warnings(args)?;
+        }
+
+        "--set-wear" => {
+            set_wear(args)?;
+        }
+
         "--set-max-time" => {
             set_max_time(args)?;
         }
@@ -64,6 +170,10 @@ pub fn arg_matcher(
         "--set-gpio" => {
             set_gpio(args)?;
         }
+
+        "--set-polarity" => {
+            set_polarity(args)?;
+        }
         
         "--set-timezone" => {
             set_timezone(args)?;
@@ -72,6 +182,42 @@ pub fn arg_matcher(
         "--set-multiplier" => { 
             set_multiplier(args)?;
         }
+
+        "--calibrate" => {
+            calibrate(args)?;
+        }
+
+        "--set-curve" => {
+            set_curve(args)?;
+        }
+
+        "--set-backend" => {
+            set_backend(args)?;
+        }
+
+        "--set-recovery" => {
+            set_recovery(args)?;
+        }
+
+        "--set-overlap" => {
+            set_overlap(args)?;
+        }
+
+        "--set-chip" => {
+            set_chip(args)?;
+        }
+
+        "--set-line" => {
+            set_line(args)?;
+        }
+
+        "--set-unit" => {
+            set_unit(args)?;
+        }
+
+        "--set-flow-rate" => {
+            set_flow_rate(args)?;
+        }
         //////////////////////////
         // Systemd Actuation
         "--entry" => {
@@ -82,6 +228,10 @@ pub fn arg_matcher(
             sysd_del_entry(args)?;
         }
 
+        "--install-safe-state" => {
+            file::sysd_install_safe_state()?;
+        }
+
         _ => return Err(InvalidArgError::new().into()),
     })
 }
@@ -92,7 +242,7 @@ fn show() -> Result<(), DynError> {
         fs::read_dir(format!("{var}/{DIR_NAME}").as_str())?
     };
     
-    Ok(for res_prof in dir_iter {
+    for res_prof in dir_iter {
         let dentry = res_prof?;
         let dpath_raw = dentry.path();
         let dprof_raw = dentry.file_name();
@@ -107,29 +257,391 @@ fn show() -> Result<(), DynError> {
             .ok_or(OsStringToUtf8Error::new())?
         ;
 
+        if prof.starts_with('.') {
+            continue;
+        }
+
         let config = fs::read_to_string(format!("{path}/config"))?;
 
-        println!("{}", format!("Profile = {prof}:\n{config}\n\n"));
-    })
+        // a config which can't be read is still shown as it is
+        let life = match FullConfig::new(prof) {
+            Ok(full) => format!(
+                "Relay life remaining: {:.1}% ({} of {} actuations used)\n{}\n",
+                wear::life_remaining(&full),
+                full.relay_count,
+                full.relay_max,
+                // ex. systemd-analyze missing
+                forecast::profile(&full, prof)
+                    .map(|forecast| forecast.to_string())
+                    .unwrap_or(String::from("Relay end of life: forecast unavailable")),
+            ),
+            Err(_) => String::new(),
+        };
+
+        println!("Profile = {prof}:\n{config}\n{life}\n");
+    }
+
+    show_relays()
 }
 
-/// arg order = (1:profile-id) (2:f64-format-time-actuated-for)
+fn show_relays() -> Result<(), DynError> {
+    for (name, relay) in relay::registry()? {
+        let installed = relay.installed
+            .map(|date| date.to_string())
+            .unwrap_or_default()
+        ;
+
+        println!(
+            "Relay = {name}:\npin = {}\ncount = {}\nmax = {}\ninstalled = {installed}\n",
+            relay.pin,
+            relay.count,
+            relay.max,
+        );
+    }
+
+    Ok(())
+}
+
+/// when the relay of every profile and every registered relay is
+/// expected to wear out, profiles driving a registered relay are
+/// forecast with the relay. Profiles and relays which can't be 
+/// forecast are reported and skipped.
+fn forecast() -> Result<(), DynError> {
+    for prof in file::profiles()? {
+        let res = FullConfig::new(&prof)
+            .and_then(|config| match config.relay {
+                Some(_) => Ok(None),
+                None => forecast::profile(&config, &prof).map(Some),
+            })
+        ;
+
+        match res {
+            Ok(Some(forecast)) => println!("Profile = {prof}: {forecast}"),
+            Ok(None) => (),
+            Err(e) => eprintln!("Profile = {prof}: {e}"),
+        }
+    }
+
+    for (name, relay) in relay::registry()? {
+        match forecast::relay(&name) {
+            Ok(rate) => {
+                let forecast = Forecast {
+                    rate,
+                    count: relay.count,
+                    max: relay.max,
+                };
+
+                println!("Relay = {name}: {forecast}");
+            }
+
+            Err(e) => eprintln!("Relay = {name}: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// the relays every profile and registered relay went through, 
+/// profiles driving a registered relay are shown with the relay.
+/// Profiles which can't be read are reported and skipped.
+fn maintenance_log() -> Result<(), DynError> {
+    let dir_iter = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        fs::read_dir(format!("{var}/{DIR_NAME}").as_str())?
+    };
+
+    for res_prof in dir_iter {
+        let prof = res_prof?
+            .file_name()
+            .into_string()
+            .map_err(|_| OsStringToUtf8Error::new())?
+        ;
+
+        if prof.starts_with('.') {
+            continue;
+        }
+
+        let config = match FullConfig::new(&prof) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Profile = {prof}: {e}");
+                continue;
+            }
+        };
+
+        if config.relay.is_some() {
+            continue;
+        }
+
+        print_maintenance(
+            &format!("Profile = {prof}"),
+            config.relay_installed,
+            config.relay_count,
+            config.relay_max,
+            &config.relay_history,
+        );
+    }
+
+    for (name, relay) in relay::registry()? {
+        print_maintenance(
+            &format!("Relay = {name}"),
+            relay.installed,
+            relay.count,
+            relay.max,
+            &relay.history,
+        );
+    }
+
+    Ok(())
+}
+
+fn print_maintenance(
+    title: &str,
+    installed: Option<Datetime>,
+    count: u64,
+    max: u64,
+    history: &[Retired],
+) {
+    let date = |date: Option<Datetime>| date
+        .map(|date| date.to_string())
+        .unwrap_or(String::from("unknown"))
+    ;
+
+    let days = |days: Option<f64>| days
+        .map(|days| format!("{days:.1} days"))
+        .unwrap_or(String::from("unknown days"))
+    ;
+
+    println!("{title}:");
+
+    for retired in history {
+        println!(
+            "installed {}, retired {} after {} of {} actuations ({})",
+            date(retired.installed),
+            retired.retired,
+            retired.count,
+            retired.max,
+            days(retired.days()),
+        );
+    }
+
+    println!(
+        "installed {}, in service with {count} of {max} actuations",
+        date(installed),
+    );
+
+    if !history.is_empty() {
+        let lasted = history
+            .iter()
+            .filter_map(Retired::days)
+            .collect::<Vec<f64>>()
+        ;
+
+        let actuations = history.iter().map(|r| r.count).sum::<u64>() / history.len() as u64;
+
+        println!(
+            "retired relays lasted {actuations} actuations and {} on average",
+            days(match lasted.is_empty() {
+                true => None,
+                false => Some(lasted.iter().sum::<f64>() / lasted.len() as f64),
+            }),
+        );
+    }
+
+    println!();
+}
+
+/// the options --exec and --dispense take before the profile id.
+struct ExecFlags {
+    simulate: bool,
+    override_wear: bool,
+}
+
+/// reads the options up to and including the profile id, 
+/// which is returned with them.
+fn exec_flags(args: &mut Args) -> Result<(ExecFlags, String), DynError> {
+    let mut flags = ExecFlags { simulate: false, override_wear: false };
+
+    loop {
+        let arg = args
+            .next()
+            .ok_or(MissingArgError::new())?
+        ;
+
+        match arg.as_str() {
+            "--simulate" => flags.simulate = true,
+            "--override-wear" => flags.override_wear = true,
+            _ => return Ok((flags, arg)),
+        }
+    }
+}
+
+impl ExecFlags {
+    /// --override-wear lets a worn out relay actuate under the 
+    /// override wear policy.
+    fn apply(&self, config: &mut FullConfig) {
+        if self.override_wear && config.wear == WearPolicy::Override {
+            config.wear = WearPolicy::Overridden;
+        }
+    }
+}
+
+/// arg order = (optional:--simulate) (optional:--override-wear)
+///             (1:profile-id) 
+///             (2:f64-format-time-actuated-for-or-amount-with-unit)
 fn exec(args: &mut Args) -> Result<(), DynError> {
-    let prof = args                               
-        .next()                                
-        .ok_or(MissingArgError::new())?        
-    ;                                          
+    let (flags, prof) = exec_flags(args)?;
 
     file::init_dirs(&prof.as_str())?;         
 
-    let time_on = args 
+    let dose = args 
         .next()                                
         .ok_or(MissingArgError::new())?        
-        .parse::<f64>()?                       
+        .parse::<Dose>()?                       
+    ;
+
+    let mut config = FullConfig::new(&prof)?;      
+    flags.apply(&mut config);
+
+    if flags.simulate {
+        FullConfig::simulate(config, dose, &prof)?;
+        return Ok(());
+    }
+
+    Ok(FullConfig::execute(config, dose, &prof)?)
+}
+
+/// same as exec but always doses by amount, an amount without a 
+/// unit is in the profiles unit.
+///
+/// arg order = (optional:--simulate) (optional:--override-wear)
+///             (1:profile-id) (2:amount-with-optional-unit)
+fn dispense(args: &mut Args) -> Result<(), DynError> {
+    let (flags, prof) = exec_flags(args)?;
+
+    file::init_dirs(&prof)?;
+
+    let raw = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    let mut config = FullConfig::new(&prof)?;
+    flags.apply(&mut config);
+
+    let dose = Dose::Amount(Amount::parse_or(&raw, config.unit)?);
+
+    if flags.simulate {
+        FullConfig::simulate(config, dose, &prof)?;
+        return Ok(());
+    }
+
+    FullConfig::execute(config, dose, &prof)
+}
+
+/// arg order = (1:profile-id)
+fn force_off(args: &mut Args) -> Result<(), DynError> {
+    let prof = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    if !FullConfig::new(&prof)?.force_off(&prof)? {
+        println!("Profile = {prof}: the pin is held by another execution, left as it is");
+    }
+
+    Ok(())
+}
+
+/// puts every profile into its safe state, a profile failing 
+/// does not stop the others from being turned off.
+fn safe_state() -> Result<(), DynError> {
+    let mut failed = false;
+
+    for prof in file::profiles()? {
+        let res = FullConfig::new(&prof)
+            .and_then(|mut config| config.safe_state(&prof))
+        ;
+
+        match res {
+            Ok(Some(true)) => {
+                println!("Profile = {prof}: off, previous actuation was interrupted");
+            }
+
+            Ok(Some(false)) => println!("Profile = {prof}: off"),
+
+            Ok(None) => println!("Profile = {prof}: busy, left as it is"),
+
+            Err(e) => {
+                eprintln!("Profile = {prof}: {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        return Err(SafeStateError::new().into());
+    }
+
+    Ok(())
+}
+
+/// arg order = (1:profile-id)
+fn acknowledge(args: &mut Args) -> Result<(), DynError> {
+    let prof = args
+        .next()
+        .ok_or(MissingArgError::new())?
     ;
 
-    let config = FullConfig::new(&prof)?;      
-    Ok(FullConfig::execute(config, time_on, &prof)?)
+    if FullConfig::new(&prof)?.acknowledge(&prof)? {
+        println!("Profile = {prof}: unfinished actuation acknowledged");
+    } else {
+        println!("Profile = {prof}: nothing to acknowledge");
+    }
+
+    Ok(())
+}
+
+/// arg order = (1:profile-id)
+fn init_profile(args: &mut Args) -> Result<(), DynError> {
+    let prof = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    getter_setter::init_profile(&prof)
+}
+
+fn migrate() -> Result<(), DynError> {
+    let mut failed = false;
+
+    for prof in file::profiles()? {
+        match getter_setter::migrate(&prof) {
+            Ok(from) if from == schema::VERSION => {
+                println!("Profile = {prof}: already at version {from}");
+            }
+
+            Ok(from) => {
+                println!(
+                    "Profile = {prof}: upgraded from version {from} to {}",
+                    schema::VERSION,
+                );
+            }
+
+            Err(e) => {
+                eprintln!("Profile = {prof}: {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        return Err(MigrateError::new().into());
+    }
+
+    Ok(())
 }
 
 /// arg order = (1:profile-id) (2:u64-format-num-actuations-set)
@@ -208,6 +720,192 @@ fn set_gpio(args: &mut Args) -> Result<(), DynError> {
     Ok(GpioPin::set(pin, &prof)?)
 }
 
+/// arg order = (1:relay-name) (2:u8-format-gpio-pin) 
+///             (3:u64-format-max-num-actuations)
+fn add_relay(args: &mut Args) -> Result<(), DynError> {
+    let name = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    let pin = args
+        .next()
+        .ok_or(MissingArgError::new())?
+        .parse::<u8>()?
+    ;
+
+    let max = args
+        .next()
+        .ok_or(MissingArgError::new())?
+        .parse::<u64>()?
+    ;
+
+    relay::add(&name, pin, max)
+}
+
+/// arg order = (1:prof-id) (2:relay-name)
+fn set_relay(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let name = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    RelayName::set(name, &prof)
+}
+
+/// reports the malformed lines of the profiles log and whether it
+/// accounts for the relay count, and makes them agree if told
+/// which side to repair.
+///
+/// arg order = (1:prof-id) (optional:count-or-log)
+fn reconcile(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    let repair = args
+        .next_if(|repair| repair.parse::<Repair>().is_ok())
+        .map(|repair| repair.parse::<Repair>())
+        .transpose()?
+    ;
+
+    let config = FullConfig::new(&prof)?;
+    let reconciliation = reconcile::reconcile(&config, &prof)?;
+
+    for (log_prof, malformed) in &reconciliation.malformed {
+        println!("Profile = {log_prof}: malformed log {malformed}");
+    }
+
+    for (log_prof, backup, malformed) in &reconciliation.legacy {
+        println!("Profile = {log_prof}: {backup} {malformed}, it was not converted");
+    }
+
+    let difference = reconciliation.difference();
+
+    println!(
+        "Profile = {prof}: {} actuations counted, {} logged",
+        reconciliation.count,
+        reconciliation.logged,
+    );
+
+    match difference {
+        0 => {
+            println!("Profile = {prof}: the count and the log agree");
+            return Ok(());
+        }
+
+        1.. => println!("Profile = {prof}: {difference} counted actuations were never logged"),
+        _ => println!("Profile = {prof}: {} logged actuations were never counted", -difference),
+    }
+
+    match repair {
+        Some(Repair::Count) => {
+            reconcile::repair(&config, &prof, &reconciliation, Repair::Count)?;
+            println!("Profile = {prof}: set the count to {}", reconciliation.logged.max(0));
+        }
+
+        Some(Repair::Log) => {
+            reconcile::repair(&config, &prof, &reconciliation, Repair::Log)?;
+            println!("Profile = {prof}: logged an adjustment of {difference} actuations");
+        }
+
+        None => println!("Profile = {prof}: add count or log to repair that side"),
+    }
+
+    Ok(())
+}
+
+/// retires the relay the profile drives (the registered relay if
+/// it names one) and starts counting its replacement from 0.
+///
+/// arg order = (1:prof-id) (optional:u64-format-max-num-actuations)
+fn replace_relay(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    let max = args
+        .next_if(|max| max.parse::<u64>().is_ok())
+        .map(|max| max.parse::<u64>())
+        .transpose()?
+    ;
+
+    let config = FullConfig::new(&prof)?;
+
+    let retired = match &config.relay {
+        Some(name) => relay::replace(name, max)?,
+        None => RelayCount::replace(&prof, max)?,
+    };
+
+    println!(
+        "Profile = {prof}: retired the relay after {} of {} actuations, its replacement starts at 0",
+        retired.count,
+        retired.max,
+    );
+
+    Ok(())
+}
+
+/// arg order = (1:prof-id) (2:comma-separated-percentages)
+fn set_wear_warnings(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let thresholds = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    WarnAt::set(thresholds, &prof)
+}
+
+/// arg order = (1:prof-id) (2:refuse-override-or-warn)
+fn set_wear(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let policy = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    Wear::set(policy, &prof)
+}
+
+/// arg order = (1:prof-id) (2:active-high-or-active-low)
+fn set_polarity(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let polarity = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    Polarity::set(polarity, &prof)
+}
+
 /// arg order = (1:prof-id) (2:chrono_tz::Tz-format-timezone-string)
 fn set_timezone(args: &mut Args) -> Result<(), DynError> {
     let prof = args 
@@ -241,12 +939,167 @@ fn set_multiplier(args: &mut Args) -> Result<(), DynError> {
         .ok_or(MissingArgError::new())?
     ;
 
-    let _ = &mult.parse::<f64>()?;
+    Multiplier::set(mult, &prof)
+}
+
+/// arg order = (1:prof-id) (2:seconds:amount-points-comma-separated)
+fn set_curve(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let curve = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    CalibrationCurve::set(curve, &prof)
+}
+
+/// arg order = (1:prof-id)
+fn calibrate(args: &mut Args) -> Result<(), DynError> {
+    let prof = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    let config = FullConfig::new(&prof)?;
+    let mut backend = gpio::backend(&config, &prof)?;
+
+    calibrate::wizard(
+        config,
+        &prof,
+        backend.as_mut(),
+        &mut io::stdin().lock(),
+        &mut io::stdout(),
+    )?;
+
+    Ok(())
+}
+
+/// arg order = (1:prof-id) (2:gpio-backend-name)
+fn set_backend(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let backend = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    GpioBackend::set(backend, &prof)
+}
+
+/// arg order = (1:prof-id) (2:log-resume-or-refuse)
+fn set_recovery(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let policy = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    Recovery::set(policy, &prof)
+}
+
+/// arg order = (1:prof-id) (2:wait-skip-or-fail)
+fn set_overlap(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let policy = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    Overlap::set(policy, &prof)
+}
+
+/// arg order = (1:prof-id) (2:gpio-character-device-path-or-name)
+fn set_chip(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let chip = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    GpioChip::set(chip, &prof)
+}
+
+/// arg order = (1:prof-id) (2:u32-format-gpio-line-offset)
+fn set_line(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let line = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    GpioLine::set(line, &prof)
+}
+
+/// arg order = (1:prof-id) (2:ml-l-floz-or-gal)
+fn set_unit(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let unit = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    DoseUnit::set(unit, &prof)
+}
+
+/// arg order = (1:prof-id) (2:units-per-second-number-or-expression)
+fn set_flow_rate(args: &mut Args) -> Result<(), DynError> {
+    let prof = args 
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
+
+    file::init_dirs(&prof)?;
+
+    let rate = args
+        .next()
+        .ok_or(MissingArgError::new())?
+    ;
 
-    Ok(Multiplier::set(mult, &prof)?)
+    FlowRate::set(rate, &prof)
 }
 
-/// arg order = (1:prof-id) (2:f64-format-time-actuated-for) 
+/// arg order = (1:prof-id) (2:f64-format-time-actuated-for-or-amount-with-unit) 
 ///             (3:systemd-timer-file-name-prefix) 
 ///             (4:time-actuated-at)
 fn sysd_entry(args: &mut Args) -> Result<(), DynError> {
@@ -257,10 +1110,10 @@ fn sysd_entry(args: &mut Args) -> Result<(), DynError> {
 
     file::init_dirs(&prof)?;
 
-    let time_on = args
+    let dose = args
         .next()
         .ok_or(MissingArgError::new())?
-        .parse::<f64>()?
+        .parse::<Dose>()?
     ;
 
     let sysd_prefix = args
@@ -273,7 +1126,7 @@ fn sysd_entry(args: &mut Args) -> Result<(), DynError> {
         .ok_or(MissingArgError::new())?
     ;
 
-    Ok(file::sysd_entry(prof, time_on, sysd_prefix, time)?)
+    Ok(file::sysd_entry(prof, dose, sysd_prefix, time)?)
 }
 
 /// arg order = (1:systemd-file-name-prefix)
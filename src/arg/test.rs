@@ -0,0 +1,49 @@
+use crate::{
+    DynError,
+    arg::*,
+    fixture,
+};
+
+fn args(args: &[&str]) -> Args {
+    args
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<String>>()
+        .into_iter()
+        .peekable()
+}
+
+/// an optional argument is only taken if it is one, the command
+/// after it still runs
+#[test]
+fn reconcile_chained_test() -> Result<(), DynError> {
+    let prof = "arg_test_reconcile";
+    fixture::setup(prof)?;
+
+    let mut rest = args(&[prof, "--help"]);
+    arg_matcher(String::from("--reconcile"), &mut rest)?;
+    assert_eq!(rest.next().as_deref(), Some("--help"));
+
+    let mut rest = args(&[prof, "count", "--help"]);
+    arg_matcher(String::from("--reconcile"), &mut rest)?;
+    assert_eq!(rest.next().as_deref(), Some("--help"));
+
+    fixture::cleanup(prof)
+}
+
+#[test]
+fn replace_relay_chained_test() -> Result<(), DynError> {
+    let prof = "arg_test_replace_relay";
+    fixture::setup(prof)?;
+
+    let mut rest = args(&[prof, "--help"]);
+    arg_matcher(String::from("--replace-relay"), &mut rest)?;
+    assert_eq!(rest.next().as_deref(), Some("--help"));
+
+    let mut rest = args(&[prof, "500", "--help"]);
+    arg_matcher(String::from("--replace-relay"), &mut rest)?;
+    assert_eq!(rest.next().as_deref(), Some("--help"));
+    assert_eq!(FullConfig::new(prof)?.relay_max, 500);
+
+    fixture::cleanup(prof)
+}
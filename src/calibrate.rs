@@ -0,0 +1,347 @@
+use crate::{
+    DynError,
+    errors::{
+        CalibrationAbortedError,
+        InvalidCurveError,
+    },
+    getter_setter::{
+        FullConfig,
+        Multiplier,
+    },
+    gpio::Backend,
+    unit::{
+        Amount,
+        VolumeUnit,
+    },
+};
+
+use std::io::{
+    BufRead,
+    Write,
+};
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+    Local,
+    SecondsFormat,
+};
+
+use serde::Deserialize;
+
+use toml_edit::{
+    Array,
+    Datetime,
+    InlineTable,
+    Table,
+    Value,
+    value,
+};
+
+#[cfg(test)]
+mod test;
+
+/// seconds actuated per trial unless told otherwise,
+/// capped to the profiles max time.
+const SECONDS: f64 = 10.0;
+
+/// trials run unless told otherwise.
+const TRIALS: usize = 3;
+
+/// one timed actuation and the amount it put out.
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+pub struct Trial {
+    pub seconds: f64,
+    pub amount: f64,
+}
+
+impl Trial {
+    /// the trial as written to the profile, ex. 
+    /// { seconds = 10.0, amount = 4.2 }
+    pub fn inline(&self) -> InlineTable {
+        let mut table = InlineTable::new();
+        table.insert("seconds", Value::from(self.seconds));
+        table.insert("amount", Value::from(self.amount));
+        table
+    }
+}
+
+/// a calibration table (calibration.curve config entry) of the
+/// amount put out after so many seconds, for pumps and valves
+/// whose output isn't proportional to the time they are on (ex.
+/// start-up lag). The points are sorted by seconds and the more 
+/// seconds the more amount.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Curve(Vec<Trial>);
+
+impl Curve {
+    pub fn new(mut points: Vec<Trial>) -> Result<Self, InvalidCurveError> {
+        if points.is_empty() {
+            return Err(InvalidCurveError::new(String::from("it has no points")));
+        }
+
+        if let Some(point) = points.iter().find(|point| {
+            !point.seconds.is_finite() 
+            || !point.amount.is_finite()
+            || point.seconds <= 0.0
+            || point.amount <= 0.0
+        }) {
+            return Err(InvalidCurveError::new(format!(
+                "{}:{} is not above 0", 
+                point.seconds, 
+                point.amount,
+            )));
+        }
+
+        points.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+
+        if let Some(pair) = points.windows(2).find(|pair| {
+            pair[0].seconds == pair[1].seconds || pair[0].amount >= pair[1].amount
+        }) {
+            return Err(InvalidCurveError::new(format!(
+                "{}:{} puts out as much or more than {}:{}",
+                pair[0].seconds,
+                pair[0].amount,
+                pair[1].seconds,
+                pair[1].amount,
+            )));
+        }
+
+        Ok(Self(points))
+    }
+
+    pub fn points(&self) -> &[Trial] {
+        &self.0
+    }
+
+    /// seconds needed to put out amount, interpolated between the
+    /// two points around it. Nothing is put out at 0 seconds, past
+    /// the last point the slope of the last two points carries on.
+    pub fn seconds(&self, amount: f64) -> f64 {
+        let mut from = Trial { seconds: 0.0, amount: 0.0 };
+        let mut to = from;
+
+        for point in &self.0 {
+            (from, to) = (to, *point);
+
+            if point.amount >= amount {
+                break;
+            }
+        }
+
+        from.seconds 
+            + (amount - from.amount) 
+            * (to.seconds - from.seconds) 
+            / (to.amount - from.amount)
+    }
+}
+
+/// the trials of a --calibrate run, amounts are in unit.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Calibration {
+    pub date: DateTime<FixedOffset>,
+    pub unit: VolumeUnit,
+    pub trials: Vec<Trial>,
+}
+
+impl Calibration {
+    /// seconds per unit over all the trials together, so a
+    /// trial weighs as much as it put out.
+    pub fn multiplier(&self) -> f64 {
+        let seconds: f64 = self.trials.iter().map(|t| t.seconds).sum();
+        let amount: f64 = self.trials.iter().map(|t| t.amount).sum();
+
+        seconds / amount
+    }
+
+    /// units put out per second over all the trials together, the
+    /// flow rate amounts are dosed at without a calibration curve.
+    pub fn flow_rate(&self) -> f64 {
+        1.0 / self.multiplier()
+    }
+
+    /// the calibration as an entry of calibration.history.
+    pub fn table(&self) -> Result<Table, DynError> {
+        let date = self.date
+            .to_rfc3339_opts(SecondsFormat::Secs, false)
+            .parse::<Datetime>()?
+        ;
+
+        let trials = self.trials
+            .iter()
+            .map(Trial::inline)
+            .collect::<Array>()
+        ;
+
+        let mut table = Table::new();
+        table.insert("date", value(date));
+        table.insert("unit", value(self.unit.to_string()));
+        table.insert("multiplier", value(self.multiplier()));
+        table.insert("flow_rate", value(self.flow_rate()));
+        table.insert("trials", value(trials));
+
+        Ok(table)
+    }
+}
+
+/// walks through calibrating the profile: asks for the seconds
+/// per trial and the number of trials, actuates each trial and
+/// asks for what it put out in the profiles unit. The multiplier
+/// and flow rate worked out from them are stored once confirmed,
+/// together with the calibration (see Multiplier::calibrate). 
+/// Returns the calibration if it was stored.
+///
+/// Trial actuations switch the relay, they are counted and logged
+/// like any other.
+pub fn wizard(
+    mut config: FullConfig,
+    prof: &str,
+    backend: &mut dyn Backend,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<Option<Calibration>, DynError> {
+    let unit = config.unit;
+    let max_time = config.max_time;
+
+    writeln!(
+        output,
+        "Calibrating {prof}: every trial actuates the relay, measure what it puts out in {unit}.",
+    )?;
+
+    let seconds = ask(
+        input,
+        output,
+        "Seconds per trial",
+        Some(&SECONDS.min(max_time).to_string()),
+        &format!("a number of seconds above 0 and at most the max time ({max_time})"),
+        |answer| answer
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| *seconds > 0.0 && *seconds <= max_time),
+    )?;
+
+    let count = ask(
+        input,
+        output,
+        "Number of trials",
+        Some(&TRIALS.to_string()),
+        "a whole number above 0",
+        |answer| answer.parse::<usize>().ok().filter(|count| *count > 0),
+    )?;
+
+    let mut trials = Vec::with_capacity(count);
+
+    for trial in 1..=count {
+        ask(
+            input,
+            output,
+            &format!("Trial {trial}/{count}: press enter to actuate for {seconds} seconds"),
+            Some(""),
+            "",
+            |_| Some(()),
+        )?;
+
+        let actuation = config.actuate_exactly(seconds, prof, backend)?;
+
+        let amount = ask(
+            input,
+            output,
+            &format!("Measured output in {unit}"),
+            None,
+            &format!("an amount above 0, ex. 12.5 or 12.5{unit}"),
+            |answer| Amount::parse_or(answer, unit)
+                .ok()
+                .map(|amount| amount.in_unit(unit))
+                .filter(|amount| *amount > 0.0),
+        )?;
+
+        trials.push(Trial { seconds: actuation.seconds, amount });
+    }
+
+    let calibration = Calibration {
+        date: Local::now().with_timezone(&config.timezone).fixed_offset(),
+        unit,
+        trials,
+    };
+
+    writeln!(
+        output,
+        "Multiplier = {} (seconds per {unit})",
+        calibration.multiplier(),
+    )?;
+    writeln!(
+        output,
+        "Flow rate = {} ({unit} per second)",
+        calibration.flow_rate(),
+    )?;
+
+    if config.curve.is_some() {
+        writeln!(
+            output,
+            "{prof} has a calibration curve, amounts are still dosed by it rather than the flow rate.",
+        )?;
+    }
+
+    let store = ask(
+        input,
+        output,
+        "Store it? (y/n)",
+        Some("y"),
+        "y or n",
+        |answer| match answer.to_ascii_lowercase().as_str() {
+            "y" | "yes" => Some(true),
+            "n" | "no" => Some(false),
+            _ => None,
+        },
+    )?;
+
+    if !store {
+        writeln!(output, "Nothing was stored.")?;
+        return Ok(None);
+    }
+
+    Multiplier::calibrate(&calibration, prof)?;
+    writeln!(output, "Stored the multiplier and flow rate of {prof}.")?;
+
+    Ok(Some(calibration))
+}
+
+/// prints prompt and reads answers until parse accepts one,
+/// hint is printed after every answer it doesn't. An empty
+/// answer is taken as default, if there is one.
+fn ask<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+    default: Option<&str>,
+    hint: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<T, DynError> {
+    loop {
+        match default {
+            Some(default) if !default.is_empty() => {
+                write!(output, "{prompt} [{default}]: ")?;
+            }
+
+            _ => write!(output, "{prompt}: ")?,
+        }
+
+        output.flush()?;
+
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Err(CalibrationAbortedError::new().into());
+        }
+
+        let answer = match (line.trim(), default) {
+            ("", Some(default)) => default,
+            (answer, _) => answer,
+        };
+
+        match parse(answer) {
+            Some(value) => return Ok(value),
+            None => writeln!(output, "expected {hint}")?,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    calibrate::*,
+    fixture::{
+        self,
+        cleanup,
+    },
+    getter_setter::*,
+    gpio::sim::Sim,
+    unit::Dose,
+};
+
+use std::{
+    env,
+    fs,
+    io::Cursor,
+};
+
+use toml_edit::DocumentMut;
+
+fn setup(prof: &str) -> Result<(), DynError> {
+    fixture::setup(prof)?;
+
+    RelayCount::set(String::from("0"), prof)?;
+    MaxTime::set(String::from("5"), prof)?;
+    DoseUnit::set(String::from("floz"), prof)?;
+
+    Ok(())
+}
+
+fn run(prof: &str, answers: &str) -> Result<(Option<Calibration>, String), DynError> {
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+    let mut output = Vec::new();
+
+    let calibration = wizard(
+        config,
+        prof,
+        &mut sim,
+        &mut Cursor::new(answers),
+        &mut output,
+    )?;
+
+    Ok((calibration, String::from_utf8(output)?))
+}
+
+#[test]
+fn wizard_test() -> Result<(), DynError> {
+    let prof = "calibrate_test_wizard";
+    setup(prof)?;
+
+    // 6 seconds is above the max time, "lots" isn't an amount
+    let answers = "6\n0.02\n2\n\n4\n\nlots\n1.5floz\ny\n";
+    let (calibration, output) = run(prof, answers)?;
+
+    assert!(output.contains("expected a number of seconds above 0 and at most the max time (5)"));
+    assert!(output.contains("expected an amount above 0"));
+
+    let calibration = calibration.ok_or("the calibration was not stored")?;
+    assert_eq!(
+        calibration.trials.iter().map(|t| t.amount).collect::<Vec<f64>>(),
+        vec![4.0, 1.5],
+    );
+    assert!(calibration.trials.iter().all(|t| t.seconds >= 0.02));
+
+    let config = FullConfig::new(prof)?;
+    assert_eq!(config.multiplier, calibration.multiplier());
+    assert_eq!(config.flow_rate, Some(calibration.flow_rate()));
+    assert_eq!(config.relay_count, 2, "the trials were not counted");
+
+    let doc = fs::read_to_string(format!(
+        "{}/{DIR_NAME}/{prof}/config",
+        env::var("XDG_CONFIG_HOME")?,
+    ))?.parse::<DocumentMut>()?;
+
+    let history = doc["calibration"]["history"]
+        .as_array_of_tables()
+        .ok_or("calibration.history is missing")?
+    ;
+    assert_eq!(history.len(), 1);
+
+    let entry = history.get(0).ok_or("calibration.history is empty")?;
+    assert_eq!(entry["unit"].as_str(), Some("floz"));
+    assert_eq!(entry["flow_rate"].as_float(), Some(calibration.flow_rate()));
+    assert!(entry["date"].as_datetime().is_some());
+    assert_eq!(entry["trials"].as_array().map(|a| a.len()), Some(2));
+
+    // a second calibration is appended to the history
+    run(prof, "0.01\n1\n\n3\n\n")?;
+
+    let raw = fs::read_to_string(format!(
+        "{}/{DIR_NAME}/{prof}/config",
+        env::var("XDG_CONFIG_HOME")?,
+    ))?;
+    assert_eq!(
+        raw.lines().filter(|line| *line == "[[calibration.history]]").count(),
+        2,
+    );
+
+    cleanup(prof)
+}
+
+#[test]
+fn declined_test() -> Result<(), DynError> {
+    let prof = "calibrate_test_declined";
+    setup(prof)?;
+
+    let (calibration, _) = run(prof, "0.01\n1\n\n2\nn\n")?;
+    assert_eq!(calibration, None);
+    assert_eq!(FullConfig::new(prof)?.multiplier, 1.0);
+
+    // the input ending early stores nothing either
+    assert!(run(prof, "0.01\n1\n").is_err());
+    assert_eq!(FullConfig::new(prof)?.multiplier, 1.0);
+
+    cleanup(prof)
+}
+
+#[test]
+fn multiplier_test() {
+    let calibration = Calibration {
+        date: chrono::Local::now().fixed_offset(),
+        unit: crate::unit::VolumeUnit::Ml,
+        trials: vec![
+            Trial { seconds: 10.0, amount: 30.0 },
+            Trial { seconds: 20.0, amount: 70.0 },
+        ],
+    };
+
+    assert_eq!(calibration.multiplier(), 0.3);
+    assert_eq!(calibration.flow_rate(), 1.0 / 0.3);
+}
+
+#[test]
+fn curve_test() -> Result<(), DynError> {
+    // 2 seconds of lag before a steady 5 per second
+    let curve = Curve::new(vec![
+        Trial { seconds: 10.0, amount: 40.0 },
+        Trial { seconds: 2.0, amount: 0.5 },
+        Trial { seconds: 4.0, amount: 10.0 },
+    ])?;
+
+    assert_eq!(curve.seconds(0.0), 0.0);
+    assert_eq!(curve.seconds(0.25), 1.0);
+    assert_eq!(curve.seconds(0.5), 2.0);
+    assert_eq!(curve.seconds(10.0), 4.0);
+    assert_eq!(curve.seconds(25.0), 7.0);
+    assert_eq!(curve.seconds(40.0), 10.0);
+    assert_eq!(curve.seconds(50.0), 12.0);
+
+    assert!(Curve::new(Vec::new()).is_err());
+    assert!(Curve::new(vec![Trial { seconds: 1.0, amount: f64::NAN }]).is_err());
+
+    Ok(())
+}
+
+/// amounts go through the curve rather than the flow rate
+/// and stay within the max time
+#[test]
+fn curve_seconds_test() -> Result<(), DynError> {
+    let prof = "calibrate_test_curve_seconds";
+    setup(prof)?;
+
+    DoseUnit::set(String::from("ml"), prof)?;
+    FlowRate::set(String::from("100"), prof)?;
+    CalibrationCurve::set(String::from("1:1,2:11"), prof)?;
+
+    let config = FullConfig::new(prof)?;
+    assert_eq!(config.request("6ml".parse::<Dose>()?)?.seconds, 1.5);
+    assert_eq!(config.request("1l".parse::<Dose>()?)?.seconds, 5.0);
+
+    cleanup(prof)
+}
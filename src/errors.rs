@@ -84,13 +84,645 @@ pub struct MalformedLogError {
 }
 
 impl MalformedLogError {
-    const MSG: &'static str = "the log is malformed; it is missing information or incorrectly formatted. If you're a user, copy your log files contents to a safe place, check your total_relay_actuations, and then use the appropriate cmdline arg to add the old relay count to your NEW log file AFTER you delete the old one from the appropriate location. RELAY_ACTUATION_COUNT IS USED TO GUARENTEE YOUR SAFETY; YOUR RELAY WILL NOT LIVE FOREVER.";
+    const MSG: &'static str = "the log is malformed; it is missing information or incorrectly formatted. If you're a user, run --reconcile on the profile to find the malformed lines and compare the log with the relay count, copy your log files contents to a safe place before fixing them by hand. RELAY_ACTUATION_COUNT IS USED TO GUARENTEE YOUR SAFETY; YOUR RELAY WILL NOT LIVE FOREVER.";
 
     pub fn new() -> MalformedLogError {
         Self { msg: Self::MSG, backtrace: Backtrace::capture() }
     }
 }
 
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidBackendError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidBackendError {
+    const MSG: &'static str = "InvalidBackend Error : the gpio backend is not one of the supported backends (rppal, cdev, sim)!";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidBackendError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidPolarityError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidPolarityError {
+    const MSG: &'static str = "InvalidPolarity Error : the polarity must be either active-high or active-low!";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidPolarityError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct ActuationInterruptedError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl ActuationInterruptedError {
+    const MSG: &'static str = "ActuationInterrupted Error : a signal stopped the actuation early, the relay was turned off and the seconds actually actuated were logged.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for ActuationInterruptedError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct CommandFailedError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl CommandFailedError {
+    const MSG: &'static str = "CommandFailed Error : an external command (ex. systemctl) exited unsuccessfully, see its output above.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for CommandFailedError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct SafeStateError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl SafeStateError {
+    const MSG: &'static str = "SafeState Error : one or more profiles could not be put into their safe state, their errors are printed above. CHECK THOSE RELAYS BY HAND.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for SafeStateError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidPolicyError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidPolicyError {
+    const MSG: &'static str = "InvalidPolicy Error : the policy is not one of the values supported by its setting, see --help!";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidPolicyError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct MalformedJournalError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl MalformedJournalError {
+    const MSG: &'static str = "MalformedJournal Error : the journal of the actuation in progress is missing information or incorrectly formatted. If you're a user, check the relay by hand, note the journal contents in the log and delete the journal file.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for MalformedJournalError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct UnacknowledgedInterruptionError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl UnacknowledgedInterruptionError {
+    const MSG: &'static str = "UnacknowledgedInterruption Error : a previous actuation of this profile never finished and its recovery policy is refuse. Check the relay, then use --ack on the profile to allow actuations again.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for UnacknowledgedInterruptionError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct LockedError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl LockedError {
+    const MSG: &'static str = "Locked Error : another execution is actuating this profile or its gpio pin and the profiles overlap policy is fail.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for LockedError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {key} on line {line}: {reason}\nBacktrace:\n{backtrace}")]
+pub struct InvalidConfigEntryError {
+    msg: &'static str,
+    key: String,
+    line: usize,
+    reason: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidConfigEntryError {
+    const MSG: &'static str = "Invalid Config Entry Error : the config file has an invalid value for";
+
+    pub fn new(key: &str, line: usize, reason: String) -> Self {
+        Self {
+            msg: Self::MSG,
+            key: key.to_string(),
+            line,
+            reason,
+            backtrace: Backtrace::capture(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\n{reason}\nBacktrace:\n{backtrace}")]
+pub struct InvalidConfigError {
+    msg: &'static str,
+    reason: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidConfigError {
+    const MSG: &'static str = "Invalid Config Error : the profiles config file could not be read";
+
+    pub fn new(reason: String) -> Self {
+        Self { msg: Self::MSG, reason, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {version}, this program reads up to version {supported}.\nBacktrace:\n{backtrace}")]
+pub struct UnsupportedVersionError {
+    msg: &'static str,
+    version: i64,
+    supported: i64,
+    backtrace: Backtrace,
+}
+
+impl UnsupportedVersionError {
+    const MSG: &'static str = "Unsupported Version Error : the config was written by a newer version of the program, config version";
+
+    pub fn new(version: i64, supported: i64) -> Self {
+        Self { msg: Self::MSG, version, supported, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct MigrateError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl MigrateError {
+    const MSG: &'static str = "Migrate Error : one or more profiles could not be upgraded, their errors are printed above.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for MigrateError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {expr}: {reason}\nBacktrace:\n{backtrace}")]
+pub struct InvalidExpressionError {
+    msg: &'static str,
+    expr: String,
+    reason: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidExpressionError {
+    const MSG: &'static str = "Invalid Expression Error : could not evaluate";
+
+    pub fn new(expr: String, reason: String) -> Self {
+        Self { msg: Self::MSG, expr, reason, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {line}\nBacktrace:\n{backtrace}")]
+pub struct MalformedConfigLineError {
+    msg: &'static str,
+    line: usize,
+    backtrace: Backtrace,
+}
+
+impl MalformedConfigLineError {
+    const MSG: &'static str = "Malformed Config Line Error : expected \"Key: value\", a # comment or a blank line on line";
+
+    pub fn new(line: usize) -> Self {
+        Self { msg: Self::MSG, line, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct ProfileExistsError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl ProfileExistsError {
+    const MSG: &'static str = "Profile Exists Error : the profile already has a config, use the --set options to change it.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for ProfileExistsError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} \"{amount}\", expected a number followed by ml, l, floz or gal, ex. 250ml\nBacktrace:\n{backtrace}")]
+pub struct InvalidAmountError {
+    msg: &'static str,
+    amount: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidAmountError {
+    const MSG: &'static str = "Invalid Amount Error : could not read the amount";
+
+    pub fn new(amount: String) -> Self {
+        Self { msg: Self::MSG, amount, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidFlowRateError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidFlowRateError {
+    const MSG: &'static str = "Invalid Flow Rate Error : the flow rate has to be a number above 0.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidFlowRateError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct MissingFlowRateError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl MissingFlowRateError {
+    const MSG: &'static str = "Missing Flow Rate Error : the profile has no flow rate to turn an amount into seconds, set one with --set-flow-rate or give it a calibration curve with --set-curve.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for MissingFlowRateError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct BusyError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl BusyError {
+    const MSG: &'static str = "Busy Error : another execution is actuating this profile or its gpio pin, try again once it is done.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for BusyError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct CalibrationAbortedError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl CalibrationAbortedError {
+    const MSG: &'static str = "Calibration Aborted Error : the input ended before the calibration was done, nothing was stored.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for CalibrationAbortedError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}, {reason}\nBacktrace:\n{backtrace}")]
+pub struct InvalidCurveError {
+    msg: &'static str,
+    reason: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidCurveError {
+    const MSG: &'static str = "Invalid Curve Error : expected seconds:amount points above 0 where more seconds put out more, ex. 2:5,10:40";
+
+    pub fn new(reason: String) -> Self {
+        Self { msg: Self::MSG, reason, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {seconds}\nBacktrace:\n{backtrace}")]
+pub struct InvalidDurationError {
+    msg: &'static str,
+    seconds: f64,
+    backtrace: Backtrace,
+}
+
+impl InvalidDurationError {
+    const MSG: &'static str = "Invalid Duration Error : the seconds to actuate for have to be a finite number of at least 0, nothing was actuated. Got";
+
+    pub fn new(seconds: f64) -> Self {
+        Self { msg: Self::MSG, seconds, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidMaxTimeError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidMaxTimeError {
+    const MSG: &'static str = "Invalid Max Time Error : the max time of the profile has to be a finite number of at least 0, nothing was actuated.";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidMaxTimeError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} ({count} of {max} actuations) and its wear policy is {policy}. Replace the relay and record it with --replace-relay, or under the override policy add --override-wear to actuate anyway.\nBacktrace:\n{backtrace}")]
+pub struct RelayWornOutError {
+    msg: &'static str,
+    count: u64,
+    max: u64,
+    policy: String,
+    backtrace: Backtrace,
+}
+
+impl RelayWornOutError {
+    const MSG: &'static str = "Relay Worn Out Error : nothing was actuated, the relay has switched as many times as it is rated for";
+
+    pub fn new(count: u64, max: u64, policy: String) -> Self {
+        Self { msg: Self::MSG, count, max, policy, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidThresholdError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidThresholdError {
+    const MSG: &'static str = "Invalid Threshold Error : expected comma separated percentages above 0 and at most 100, ex. 80,95";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidThresholdError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} \"{name}\", only letters, digits, - and _ are allowed\nBacktrace:\n{backtrace}")]
+pub struct InvalidRelayNameError {
+    msg: &'static str,
+    name: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidRelayNameError {
+    const MSG: &'static str = "Invalid Relay Name Error : can't name a relay";
+
+    pub fn new(name: String) -> Self {
+        Self { msg: Self::MSG, name, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {name}\nBacktrace:\n{backtrace}")]
+pub struct RelayExistsError {
+    msg: &'static str,
+    name: String,
+    backtrace: Backtrace,
+}
+
+impl RelayExistsError {
+    const MSG: &'static str = "Relay Exists Error : there already is a relay named";
+
+    pub fn new(name: String) -> Self {
+        Self { msg: Self::MSG, name, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {name}, add it with --add-relay\nBacktrace:\n{backtrace}")]
+pub struct UnknownRelayError {
+    msg: &'static str,
+    name: String,
+    backtrace: Backtrace,
+}
+
+impl UnknownRelayError {
+    const MSG: &'static str = "Unknown Relay Error : there is no relay named";
+
+    pub fn new(name: String) -> Self {
+        Self { msg: Self::MSG, name, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} (backend.pin is {pin}, the relay is on {relay_pin}), remove backend.pin or change one of them\nBacktrace:\n{backtrace}")]
+pub struct RelayPinMismatchError {
+    msg: &'static str,
+    pin: u8,
+    relay_pin: u8,
+    backtrace: Backtrace,
+}
+
+impl RelayPinMismatchError {
+    const MSG: &'static str = "Relay Pin Mismatch Error : the profile and the relay it names are on different pins";
+
+    pub fn new(pin: u8, relay_pin: u8) -> Self {
+        Self { msg: Self::MSG, pin, relay_pin, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg}\nBacktrace:\n{backtrace}")]
+pub struct InvalidRepairError {
+    msg: &'static str,
+    backtrace: Backtrace,
+}
+
+impl InvalidRepairError {
+    const MSG: &'static str = "Invalid Repair Error : --reconcile repairs either the count (count) or the log (log)";
+
+    pub fn new() -> Self {
+        Self { msg: Self::MSG, backtrace: Backtrace::capture() }
+    }
+}
+
+impl Default for InvalidRepairError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {key} = {value}\nBacktrace:\n{backtrace}")]
+pub struct InvalidCountError {
+    msg: &'static str,
+    key: String,
+    value: String,
+    backtrace: Backtrace,
+}
+
+impl InvalidCountError {
+    const MSG: &'static str = "Invalid Count Error : a number of actuations has to be a whole number of at least 0, nothing was changed so the relays wear isn't lost. Fix it by hand, got";
+
+    pub fn new(key: &str, value: String) -> Self {
+        Self { msg: Self::MSG, key: key.to_string(), value, backtrace: Backtrace::capture() }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{msg} {entries}\nBacktrace:\n{backtrace}")]
+pub struct DoseConflictError {
+    msg: &'static str,
+    entries: String,
+    backtrace: Backtrace,
+}
+
+impl DoseConflictError {
+    const MSG: &'static str = "Dose Conflict Error : every entry of a profile runs the same service, delete the entries with another dose (--del-entry) or use another profile. Entries:";
+
+    pub fn new(entries: &[String]) -> Self {
+        Self { msg: Self::MSG, entries: entries.join(", "), backtrace: Backtrace::capture() }
+    }
+}
+
 //#[derive(Debug, Error)]
 //#[error("{msg}\nBacktrace:\n{backtrace}")]
 //pub struct MaxOutputError {
@@ -0,0 +1,328 @@
+use crate::{
+    DynError,
+    errors::InvalidExpressionError,
+};
+
+use std::{
+    iter::Peekable,
+    str::CharIndices,
+};
+
+#[cfg(test)]
+mod test;
+
+/// constants which can be used by name (case insensitive),
+/// volumes are in millilitres and times in seconds. A number
+/// directly followed by a name is multiplied by it, ex. 8floz.
+pub const CONSTANTS: &[(&str, &str)] = &[
+    ("ml", "1"),
+    ("l", "1000"),
+    ("floz", "29.5735295625"),
+    ("gal", "3785.411784"),
+    ("s", "1"),
+    ("min", "60"),
+    ("h", "3600"),
+];
+
+/// evaluates an arithmetic expression of decimal numbers, named
+/// CONSTANTS, + - * / and parentheses, ex. (1/0.0338)*1.25.
+/// Everything is computed as an exact fraction and only rounded
+/// once at the end, so 1/3*3 is exactly 1.
+pub fn eval(src: &str) -> Result<f64, DynError> {
+    let mut parser = Parser {
+        src,
+        chars: src.char_indices().peekable(),
+    };
+
+    let value = parser.sum()?;
+
+    if let Some((at, c)) = parser.peek() {
+        return Err(parser.error(at, format!("unexpected '{c}'")).into());
+    }
+
+    Ok(value.to_f64())
+}
+
+/// a fraction in lowest terms with a positive denominator.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+
+        let gcd = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+
+        Some(Self {
+            num: num.checked_div(gcd)?.checked_mul(sign)?,
+            den: den.checked_div(gcd)?.checked_mul(sign)?,
+        })
+    }
+
+    fn add(self, rhs: Self) -> Option<Self> {
+        Self::new(
+            self.num.checked_mul(rhs.den)?
+                .checked_add(rhs.num.checked_mul(self.den)?)?,
+            self.den.checked_mul(rhs.den)?,
+        )
+    }
+
+    fn neg(self) -> Option<Self> {
+        Some(Self { num: self.num.checked_neg()?, den: self.den })
+    }
+
+    fn mul(self, rhs: Self) -> Option<Self> {
+        Self::new(
+            self.num.checked_mul(rhs.num)?,
+            self.den.checked_mul(rhs.den)?,
+        )
+    }
+
+    fn recip(self) -> Option<Self> {
+        Self::new(self.den, self.num)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    // only 0 when both are 0, which Ratio::new never passes
+    i128::try_from(a).unwrap_or(1).max(1)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, at: usize, reason: String) -> InvalidExpressionError {
+        InvalidExpressionError::new(
+            self.src.to_string(),
+            format!("{reason} at character {}", at + 1),
+        )
+    }
+
+    fn overflow(&self, at: usize) -> InvalidExpressionError {
+        self.error(at, String::from("too large to compute exactly"))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<(usize, char)> {
+        self.skip_whitespace();
+        self.chars.peek().copied()
+    }
+
+    /// sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Ratio, InvalidExpressionError> {
+        let mut value = self.product()?;
+
+        while let Some((at, op @ ('+' | '-'))) = self.peek() {
+            self.chars.next();
+
+            let mut rhs = self.product()?;
+
+            if op == '-' {
+                rhs = rhs.neg().ok_or_else(|| self.overflow(at))?;
+            }
+
+            value = value.add(rhs).ok_or_else(|| self.overflow(at))?;
+        }
+
+        Ok(value)
+    }
+
+    /// product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<Ratio, InvalidExpressionError> {
+        let mut value = self.unary()?;
+
+        while let Some((at, op @ ('*' | '/'))) = self.peek() {
+            self.chars.next();
+
+            let mut rhs = self.unary()?;
+
+            if op == '/' {
+                rhs = rhs
+                    .recip()
+                    .ok_or_else(|| self.error(at, String::from("division by zero")))?
+                ;
+            }
+
+            value = value.mul(rhs).ok_or_else(|| self.overflow(at))?;
+        }
+
+        Ok(value)
+    }
+
+    /// unary := ('-' | '+') unary | primary
+    fn unary(&mut self) -> Result<Ratio, InvalidExpressionError> {
+        match self.peek() {
+            Some((at, '-')) => {
+                self.chars.next();
+                self.unary()?.neg().ok_or_else(|| self.overflow(at))
+            }
+
+            Some((_, '+')) => {
+                self.chars.next();
+                self.unary()
+            }
+
+            _ => self.primary(),
+        }
+    }
+
+    /// primary := number name? | name | '(' sum ')'
+    fn primary(&mut self) -> Result<Ratio, InvalidExpressionError> {
+        match self.peek() {
+            Some((at, '(')) => {
+                self.chars.next();
+                let value = self.sum()?;
+
+                match self.peek() {
+                    Some((_, ')')) => {
+                        self.chars.next();
+                        Ok(value)
+                    }
+
+                    _ => Err(self.error(at, String::from("unclosed '('"))),
+                }
+            }
+
+            Some((at, c)) if c.is_ascii_digit() || c == '.' => {
+                let number = self.number(at)?;
+
+                // 8floz is 8 * floz
+                match self.chars.peek() {
+                    Some((at, c)) if c.is_alphabetic() => {
+                        let at = *at;
+                        let name = self.name(at)?;
+                        number.mul(name).ok_or_else(|| self.overflow(at))
+                    }
+
+                    _ => Ok(number),
+                }
+            }
+
+            Some((at, c)) if c.is_alphabetic() => self.name(at),
+
+            Some((at, c)) => Err(self.error(at, format!("unexpected '{c}'"))),
+
+            None => Err(self.error(
+                self.src.len(),
+                String::from("expected a number"),
+            )),
+        }
+    }
+
+    /// a decimal number with an optional exponent, ex. 0.0338 or
+    /// 2.5e-3, read exactly.
+    fn number(&mut self, at: usize) -> Result<Ratio, InvalidExpressionError> {
+        let mut digits = String::new();
+        let mut decimals: i32 = 0;
+        let mut seen_point = false;
+
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| {
+            c.is_ascii_digit() || *c == '.'
+        }) {
+            if c == '.' {
+                if seen_point {
+                    return Err(self.error(at, String::from("malformed number")));
+                }
+
+                seen_point = true;
+            } else {
+                digits.push(c);
+
+                if seen_point {
+                    decimals += 1;
+                }
+            }
+        }
+
+        if digits.is_empty() {
+            return Err(self.error(at, String::from("malformed number")));
+        }
+
+        let mut exponent: i32 = 0;
+
+        if self.chars.next_if(|(_, c)| matches!(c, 'e' | 'E')).is_some() {
+            let mut raw = String::new();
+
+            if let Some((_, sign)) = self.chars.next_if(|(_, c)| matches!(c, '+' | '-')) {
+                raw.push(sign);
+            }
+
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                raw.push(c);
+            }
+
+            exponent = raw
+                .parse::<i32>()
+                .map_err(|_| self.error(at, String::from("malformed exponent")))?
+            ;
+        }
+
+        let num = digits
+            .parse::<i128>()
+            .map_err(|_| self.overflow(at))?
+        ;
+
+        let shift = exponent
+            .checked_sub(decimals)
+            .ok_or_else(|| self.overflow(at))?
+        ;
+
+        let pow = 10i128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or_else(|| self.overflow(at))?
+        ;
+
+        let value = if shift >= 0 {
+            num.checked_mul(pow).and_then(|num| Ratio::new(num, 1))
+        } else {
+            Ratio::new(num, pow)
+        };
+
+        value.ok_or_else(|| self.overflow(at))
+    }
+
+    fn name(&mut self, at: usize) -> Result<Ratio, InvalidExpressionError> {
+        let mut name = String::new();
+
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| {
+            c.is_alphanumeric() || *c == '_'
+        }) {
+            name.push(c);
+        }
+
+        let (_, value) = CONSTANTS
+            .iter()
+            .find(|(constant, _)| constant.eq_ignore_ascii_case(&name))
+            .ok_or_else(|| self.error(at, format!("unknown constant {name}")))?
+        ;
+
+        let mut parser = Parser {
+            src: value,
+            chars: value.char_indices().peekable(),
+        };
+
+        parser.number(0)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{
+    DynError,
+    expr::*,
+};
+
+#[test]
+fn eval_test() -> Result<(), DynError> {
+    assert_eq!(eval("2.5")?, 2.5);
+    assert_eq!(eval(" 1 + 2 * 3 ")?, 7.0);
+    assert_eq!(eval("(1 + 2) * 3")?, 9.0);
+    assert_eq!(eval("-2 - -3")?, 1.0);
+    assert_eq!(eval("2.5e-3")?, 0.0025);
+    // 1.25 / 0.0338 in floats is off in the last digit
+    assert_eq!(eval("(1/0.0338)*1.25")?, 12500.0 / 338.0);
+
+    // exact until the end, floats would give 0.30000000000000004
+    assert_eq!(eval("0.1 + 0.2")?, 0.3);
+    assert_eq!(eval("1/3*3")?, 1.0);
+
+    Ok(())
+}
+
+#[test]
+fn constants_test() -> Result<(), DynError> {
+    assert_eq!(eval("8floz")?, 236.5882365);
+    assert_eq!(eval("1gal / 1L")?, 3.785411784);
+    assert_eq!(eval("2 * min")?, 120.0);
+    assert_eq!(eval("60/1gal")?, 60_000_000.0 / 3_785_411_784.0);
+
+    Ok(())
+}
+
+#[test]
+fn invalid_test() {
+    for src in ["", "1 +", "(1 + 2", "1 / 0", "1 / (2 - 2)", "2 cups", "1..2", "3)", "1e"] {
+        assert!(eval(src).is_err(), "{src:?} should not evaluate");
+    }
+
+    let err = eval("2 * x").err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("unknown constant x at character 5"), "{err}");
+}
@@ -1,21 +1,55 @@
 use crate::{
     errors::{
+        CommandFailedError,
+        DoseConflictError,
         InvalidArgError,
         OsStringToUtf8Error,
     },
     DIR_NAME,
     getter_setter::FullConfig,
+    gpio::{
+        Actuation,
+        Outcome,
+    },
+    journal::{
+        Journal,
+        Policy,
+    },
+    lock::{
+        self,
+        OverlapPolicy,
+    },
+    record::{
+        self,
+        Event,
+        Record,
+    },
+    unit::{
+        Dose,
+        Request,
+    },
     DynError,
 };
 
 use std::{
-    fs,
+    fs::{
+        self,
+        File,
+    },
     env,
     str,
-    process::Command,
+    io::Write,
+    path::Path,
+    process::{
+        self,
+        Command,
+    },
 };
 
-use chrono::Local;
+use chrono::{
+    Local,
+    NaiveDateTime,
+};
 
 #[cfg(test)]
 mod test;
@@ -29,64 +63,259 @@ pub fn init_dirs(prof: &str) -> Result<(), DynError> {
     Ok(fs::create_dir_all(&dir)?)
 }
 
+/// every profile id, in no particular order. Hidden entries
+/// (ex. .locks) hold program state and are not profiles.
+pub fn profiles() -> Result<Vec<String>, DynError> {
+    let dir = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}")
+    };
+
+    let mut profs = Vec::new();
+
+    for dentry in fs::read_dir(dir)? {
+        let dentry = dentry?;
+
+        if !dentry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let prof = dentry
+            .file_name()
+            .into_string()
+            .map_err(|_| OsStringToUtf8Error::new())?
+        ;
+
+        if !prof.starts_with('.') {
+            profs.push(prof);
+        }
+    }
+
+    Ok(profs)
+}
+
+/// the record of an event of the profile, timestamped now in
+/// the profiles timezone.
+fn record(config: &FullConfig, event: Event, id: &str) -> Record {
+    Record {
+        profile: id.to_string(),
+        pin: config.gpio_pin,
+        timestamp: Local::now()
+            .with_timezone(&config.timezone)
+            .fixed_offset(),
+        event,
+    }
+}
+
+/// records a finished (or cut short) actuation. The amount asked
+/// for is kept when dosing by amount.
 pub fn log(
     config: &FullConfig,
-    time_on: f64,
+    actuation: &Actuation,
+    request: &Request,
     id: &str,
 ) -> Result<(), DynError> {
-    let timestamp = Local::now()
-        .with_timezone(&config.timezone)
-        .to_rfc2822()
-    ;
+    let event = Event::Entry {
+        requested_seconds: request.requested,
+        actual_seconds: actuation.seconds,
+        clamped: request.clamped(),
+        outcome: actuation.outcome,
+        requested_amount: request.amount,
+        multiplier: config.multiplier,
+        max_time: config.max_time,
+    };
 
-    let log = format!(
-r#"[ENTRY]
-Seconds-Actuated:{time_on}
-Timestamp:{timestamp}
-GpioPin:{}
-Multiplier:{}
-Max-Time:{}"#,
-        &config.gpio_pin,
-        &config.multiplier,
-        &config.max_time,
-    );
+    record::append(&record(config, event, id), id)
+}
 
-    let path = {
-        let var = env::var("XDG_CONFIG_HOME")?;
-        format!("{var}/{DIR_NAME}/{id}/log")
+/// records that the pin was put into its safe (off) state and 
+/// whether the actuation before it had been interrupted.
+pub fn log_safe_state(
+    config: &FullConfig,
+    interrupted: bool,
+    id: &str,
+) -> Result<(), DynError> {
+    let previous = match interrupted {
+        true => Outcome::Interrupted,
+        false => Outcome::Completed,
     };
 
-    Ok(if fs::exists(&path)? {
-        let contents = fs::read_to_string(&path)?;
-        fs::write(&path, format!("{contents}\n{log}"))?;
-    } else {
-        fs::write(&path, log)?;
-    })
+    record::append(&record(config, Event::SafeState { previous }, id), id)
+}
+
+/// records an actuation which never finished, the relay was on
+/// for at least its actual seconds.
+pub fn log_interrupted(
+    config: &FullConfig,
+    journal: &Journal,
+    id: &str,
+) -> Result<(), DynError> {
+    let event = Event::Interrupted {
+        requested_seconds: journal.requested,
+        actual_seconds: journal.elapsed,
+        started: journal.started.with_timezone(&config.timezone).fixed_offset(),
+        recovery: config.recovery,
+    };
+
+    let mut interrupted = record(config, event, id);
+    interrupted.pin = journal.gpio_pin;
+
+    record::append(&interrupted, id)
+}
+
+/// records an execution which did not actuate because another
+/// one held the profile or its pin.
+pub fn log_skipped(
+    config: &FullConfig,
+    dose: Dose,
+    id: &str,
+) -> Result<(), DynError> {
+    let event = Event::Skipped {
+        requested: dose,
+        overlap: config.overlap,
+    };
+
+    record::append(&record(config, event, id), id)
+}
+
+/// records a --reconcile of the log with the relay count,
+/// adjustment is the number of actuations counted but never 
+/// logged (negative if more were logged than counted).
+pub fn log_reconciled(
+    config: &FullConfig,
+    adjustment: i64,
+    id: &str,
+) -> Result<(), DynError> {
+    let event = Event::Reconciled {
+        adjustment,
+        relay_count: config.relay_count,
+    };
+
+    record::append(&record(config, event, id), id)
+}
+
+/// records the relay passing one of its wear thresholds.
+pub fn log_wear(
+    config: &FullConfig,
+    threshold: f64,
+    id: &str,
+) -> Result<(), DynError> {
+    let event = Event::Wear {
+        percent_used: threshold,
+        relay_count: config.relay_count,
+        relay_max: config.relay_max,
+    };
+
+    record::append(&record(config, event, id), id)
+}
+
+/// records an execution of a worn out relay, action is what
+/// the wear policy made of it (refused, warned or overridden).
+pub fn log_worn_out(
+    config: &FullConfig,
+    action: &str,
+    id: &str,
+) -> Result<(), DynError> {
+    let event = Event::WornOut {
+        action: action.to_string(),
+        relay_count: config.relay_count,
+        relay_max: config.relay_max,
+    };
+
+    record::append(&record(config, event, id), id)
+}
+
+/// replaces the file at path with contents through a temporary
+/// file which is synced and renamed over it, so a power cut 
+/// leaves either the old or the new file but never a truncated
+/// one. Concurrent writers of the same file have to be 
+/// serialized by the caller (see lock::WriteLock).
+pub fn write_atomic(path: &str, contents: &str) -> Result<(), DynError> {
+    let tmp = format!("{path}.{}.tmp", process::id());
+
+    let written = File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path))
+    ;
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    // the rename itself is only durable once the directory is
+    if let Some(dir) = Path::new(path).parent() {
+        File::open(dir)?.sync_all()?;
+    }
+
+    Ok(())
 }
 
 const SYSTEMD_DIR: &'static str = "/etc/systemd/system";
 const IDENTIFIER: &'static str = "atg-actuator";
+const BIN: &str = "/usr/local/bin/switch";
 
 /// generates a timer file based on the inputed timestamp and 
-/// a service file which takes a time actuated for and a profile
-/// to look for configuration values within. 
+/// a service file which takes a dose (a time actuated for or an 
+/// amount, see unit::Dose) and a profile
+/// to look for configuration values within. The service is limited
+/// to the profiles MaxTime by systemd and forces the pin off once
+/// it stops for any reason, so the profile has to be configured first.
+/// Every entry of a profile runs its one service, an entry with 
+/// another dose than the existing ones is refused.
 ///
 /// There are lots of config values I could add to getter_setter 
 /// here once the project is more mature. It would make sense 
 /// to have defaults, which would be what I am using now because legacy
 ///
-/// arg order = (1:prof-id) (2:f64-format-time-actuated-for) 
+/// arg order = (1:prof-id) (2:time-actuated-for-or-amount) 
 ///             (3:systemd-timer-file-name-prefix) 
 ///             (4:time-actuated-at-in-systemd-format-(ex. = 00:00:00))
 ///
 /// arg number 4 parsing is taken care of by systemd-analyze calendar cmd
 pub fn sysd_entry(
     prof: String,
-    time_on: f64,
+    dose: Dose,
     sysd_file_prefix: String,
     time: String,
 ) -> Result<(), DynError> {
     verify_time(&time)?;
+
+    let config = FullConfig::new(&prof)?;
+
+    // a dose the profile can't actuate would only fail at the timer
+    config.request(dose)?;
+
+    let service_path = format!("{SYSTEMD_DIR}/{IDENTIFIER}_{prof}.service");
+    let runtime_max = runtime_max(&config, &prof)?;
+    let exec_start = format!("ExecStart=+{BIN} --exec {prof} {dose}");
+    let service_cont = format!(
+r#"[Service]
+{exec_start}
+ExecStopPost=+{BIN} --force-off {prof}
+RuntimeMaxSec={runtime_max}
+[Unit]
+Description=actuates gpio pins for the switch program"#
+    );
+
+    if fs::exists(&service_path)? 
+    && !fs::read_to_string(&service_path)?.lines().any(|line| line == exec_start) 
+    {
+        let others = timers()?
+            .into_iter()
+            .filter(|(prefix, timer_prof, _)| *timer_prof == prof && *prefix != sysd_file_prefix)
+            .map(|(prefix, _, _)| prefix)
+            .collect::<Vec<String>>()
+        ;
+
+        if !others.is_empty() {
+            return Err(DoseConflictError::new(&others).into());
+        }
+    }
+
     let timer_path = format!("{SYSTEMD_DIR}/{IDENTIFIER}_{sysd_file_prefix}.timer");
     let timer_cont = format!(
 r#"[Timer]
@@ -102,26 +331,188 @@ WantedBy=timers.target"#
 
     fs::write(timer_path, timer_cont)?;
 
-    let service_path = format!("{SYSTEMD_DIR}/{IDENTIFIER}_{prof}.service");
+    if 
+        fs::exists(&service_path)? 
+    && 
+        fs::read_to_string(&service_path)? == service_cont 
+    {
+        return Ok(());
+    }
+
+    fs::write(&service_path, service_cont)?;
+
+    Ok(())
+}
+
+/// seconds systemd lets an actuation run before killing it, 
+/// ExecStopPost turns the pin off after the kill. It is the worst
+/// case of a healthy run plus some slack for loading the config 
+/// and logging: the profile actuating for its MaxTime, after 
+/// resuming an unfinished actuation under the resume recovery 
+/// policy, and under the wait overlap policy after waiting for 
+/// one run of every other profile on its pin (each profile has 
+/// one service, which systemd never runs twice at once).
+fn runtime_max(config: &FullConfig, prof: &str) -> Result<u64, DynError> {
+    const SLACK: f64 = 10.0;
+
+    let mut worst = worst_run(config);
+
+    if config.overlap == OverlapPolicy::Wait {
+        for other in profiles()? {
+            if other == prof {
+                continue;
+            }
+
+            // a profile which can't be loaded can't actuate either
+            let Ok(other) = FullConfig::new(&other) else {
+                continue;
+            };
+
+            if lock::pin_id(&other) == lock::pin_id(config) {
+                worst += worst_run(&other);
+            }
+        }
+    }
+
+    Ok((worst + SLACK).ceil() as u64)
+}
+
+/// the longest a run of the profile actuates for.
+fn worst_run(config: &FullConfig) -> f64 {
+    let max_time = config.max_time.max(0.0);
+
+    match config.recovery {
+        Policy::Resume => max_time * 2.0,
+        Policy::Log | Policy::Refuse => max_time,
+    }
+}
+
+/// writes and enables a oneshot service which runs --safe-state 
+/// early during boot, before anything else can touch the pins.
+pub fn sysd_install_safe_state() -> Result<(), DynError> {
+    let name = format!("{IDENTIFIER}-safe-state.service");
     let service_cont = format!(
-r#"[Service]
-ExecStart=+/usr/local/bin/switch --exec {prof} {time_on}
-[Unit]
-Description=actuates gpio pins for the switch program"#
+r#"[Unit]
+Description=drives every gpio pin of the switch program to its off level
+DefaultDependencies=no
+After=local-fs.target
+Before=sysinit.target shutdown.target
+Conflicts=shutdown.target
+[Service]
+Type=oneshot
+EnvironmentFile=-/etc/environment
+ExecStart=+{BIN} --safe-state
+[Install]
+WantedBy=sysinit.target"#
     );
 
+    fs::write(format!("{SYSTEMD_DIR}/{name}"), service_cont)?;
 
-    if fs::exists(&service_path)? {
-        if fs::read_to_string(&service_path)? == service_cont {
-            return Ok(());
-        }
-    } else {
-        fs::write(&service_path, service_cont)?;
+    let status = Command::new("systemctl")
+        .args(["enable", &name])
+        .status()?
+    ;
+
+    if !status.success() {
+        return Err(CommandFailedError::new().into());
     }
 
     Ok(())
 }
 
+/// the profile and OnCalendar expression of every timer entry,
+/// there are none where systemd isn't.
+pub fn sysd_timers() -> Result<Vec<(String, String)>, DynError> {
+    Ok(timers()?
+        .into_iter()
+        .map(|(_, prof, calendar)| (prof, calendar))
+        .collect()
+    )
+}
+
+/// the file name prefix, profile and OnCalendar expression of 
+/// every timer entry.
+fn timers() -> Result<Vec<(String, String, String)>, DynError> {
+    let mut timers = Vec::new();
+
+    if !fs::exists(SYSTEMD_DIR)? {
+        return Ok(timers);
+    }
+
+    let prefix = format!("{IDENTIFIER}_");
+    let unit = format!("Unit={IDENTIFIER}_");
+
+    for file in fs::read_dir(SYSTEMD_DIR)? {
+        let file = file?;
+        let name = file
+            .file_name()
+            .into_string()
+            .map_err(|_| OsStringToUtf8Error::new())?
+        ;
+
+        let Some(entry) = name
+            .strip_prefix(&prefix)
+            .and_then(|name| name.strip_suffix(".timer")) 
+        else {
+            continue;
+        };
+
+        let contents = fs::read_to_string(file.path())?;
+
+        let prof = contents
+            .lines()
+            .find_map(|line| line.strip_prefix(&unit)?.strip_suffix(".service"))
+        ;
+
+        let calendar = contents
+            .lines()
+            .find_map(|line| line.strip_prefix("OnCalendar="))
+        ;
+
+        if let (Some(prof), Some(calendar)) = (prof, calendar) {
+            timers.push((entry.to_string(), prof.to_string(), calendar.to_string()));
+        }
+    }
+
+    Ok(timers)
+}
+
+/// the next (at most iterations) times systemd will elapse the
+/// calendar expression at, none if it never will again.
+pub fn sysd_elapses(
+    calendar: &str, 
+    iterations: usize,
+) -> Result<Vec<NaiveDateTime>, DynError> {
+    let out = Command::new("systemd-analyze")
+        .args(["calendar", &format!("--iterations={iterations}"), calendar])
+        .output()?
+    ;
+
+    if !out.status.success() {
+        return Err(CommandFailedError::new().into());
+    }
+
+    Ok(elapses(str::from_utf8(&out.stdout)?))
+}
+
+/// reads the times out of systemd-analyze calendar, ex. 
+/// "    Next elapse: Sun 2026-10-18 18:00:00 UTC" and
+/// "       Iter. #2: Mon 2026-10-19 06:00:00 UTC"
+fn elapses(out: &str) -> Vec<NaiveDateTime> {
+    out
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("Next elapse:") || line.starts_with("Iter. #"))
+        .filter_map(|line| {
+            let (_, time) = line.split_once(": ")?;
+            let mut fields = time.split_whitespace().skip(1);
+            let time = format!("{} {}", fields.next()?, fields.next()?);
+
+            NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S").ok()
+        })
+        .collect()
+}
+
 fn verify_time(time: &str) -> Result<(), DynError> {
     let out = Command::new("systemd-analyze")
         .args(["calendar", time])
@@ -206,7 +597,7 @@ pub fn sysd_cleanup_unused_service() -> Result<(), DynError> {
         ;
 
         if 
-            file_path.contains(IDENTIFIER) 
+            file_path.contains(&discrim) 
         &&
             file_path.ends_with(".service") 
         { 
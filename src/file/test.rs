@@ -2,19 +2,50 @@ use crate::{
     DIR_NAME,
     file::*,
     DynError,
-    getter_setter::FullConfig,
+    getter_setter::*,
+    journal::Policy,
+    lock::OverlapPolicy,
+    wear::WearPolicy,
+    unit::{
+        Dose,
+        Request,
+        VolumeUnit,
+    },
+    gpio::{
+        BackendKind,
+        ActiveLevel,
+        Actuation,
+        Outcome,
+    },
 };
 
 use std::{
     env,
     fs,
+    sync::Mutex,
 };
 
 use chrono_tz::Tz;
-use chrono::DateTime;
 
 const PROF: &'static str = "test";
 
+/// sysd_cleanup_unused_service removes the services of every 
+/// test, the tests writing to the systemd dir take turns.
+static SYSD: Mutex<()> = Mutex::new(());
+
+fn sysd_setup(prof: &str, pin: &str) -> Result<(), DynError> {
+    init_dirs(prof)?;
+    RelayCount::set(String::from("0"), prof)?;
+    RelayMax::set(String::from("100"), prof)?;
+    MaxTime::set(String::from("20.5"), prof)?;
+    // a pin no other profile is on, see runtime_max
+    GpioPin::set(String::from(pin), prof)?;
+    Timezone::set(String::from("UTC"), prof)?;
+    Multiplier::set(String::from("1.0"), prof)?;
+
+    Ok(())
+}
+
 fn cleanup_dirs() -> Result<(), DynError> {
     let path = {
         let var = env::var("XDG_CONFIG_HOME")?;
@@ -64,49 +95,72 @@ fn log_test() -> Result<(), DynError> {
     init_dirs(PROF)?;
 
     let cfg = FullConfig {
+        relay: None,
         relay_count: 10,
         relay_max: 12, 
+        relay_installed: None,
+        relay_history: Vec::new(),
+        warn_at: vec![80.0, 95.0],
+        wear: WearPolicy::Override,
         max_time: 14.0,
         gpio_pin: 8, 
         timezone: Tz::from_str_insensitive("America/New_York")?,
         multiplier: 5.0,
+        backend: BackendKind::Rppal,
+        gpio_chip: String::from("/dev/gpiochip0"),
+        gpio_line: 8,
+        polarity: ActiveLevel::High,
+        recovery: Policy::Log,
+        overlap: OverlapPolicy::Wait,
+        unit: VolumeUnit::Ml,
+        flow_rate: None,
+        curve: None,
     };
 
-    log(&cfg, 12.0, PROF)?;
+    let actuation = Actuation {
+        seconds: 12.0,
+        outcome: Outcome::Completed,
+    };
+
+    log(&cfg, &actuation, &Request::exactly(12.0), PROF)?;
+    log(&cfg, &actuation, &Request::exactly(12.0), PROF)?;
 
     let path = {
         let var = env::var("XDG_CONFIG_HOME")?;
         format!("{var}/{DIR_NAME}/{PROF}/log")
     };
 
+    // one line per record, appended
     let raw = fs::read_to_string(path)?;
-    let (time_vec, log): (Vec<&str>, Vec<&str>) = raw
-        .lines()
-        .partition(
-            |line| line.contains("Timestamp:")    
-        )
-    ;
+    assert_eq!(raw.lines().count(), 2);
 
-    let timestamp = time_vec[0]
-        .split(':')
-        .skip(1)
-        .collect::<Vec<&str>>()
-        .join(":")
-    ;
+    let line = raw.lines().next().unwrap_or_default();
+    assert!(line.starts_with(r#"{"profile":"test","pin":8,"timestamp":""#), "{line}");
+    assert!(line.ends_with(
+        r#"","kind":"entry","requested_seconds":12.0,"actual_seconds":12.0,"clamped":false,"outcome":"completed","multiplier":5.0,"max_time":14.0}"#
+    ), "{line}");
 
-    DateTime::parse_from_rfc2822(&timestamp)?;
+    let log = record::read(PROF)?;
+    assert!(log.malformed.is_empty());
+    assert_eq!(log.records.len(), 2);
 
-    // for some reason rust formats f64 numbers 
-    // into strings which omit the trailing zero!
+    // the timestamp is in the profiles timezone
+    let record = &log.records[0];
     assert_eq!(
-        log.join("\n"),
-        String::from(
-r#"[ENTRY]
-Seconds-Actuated:12
-GpioPin:8
-Multiplier:5
-Max-Time:14"#
-        ),
+        record.timestamp.offset(),
+        Local::now().with_timezone(&cfg.timezone).fixed_offset().offset(),
+    );
+    assert_eq!(
+        record.event,
+        Event::Entry {
+            requested_seconds: 12.0,
+            actual_seconds: 12.0,
+            clamped: false,
+            outcome: Outcome::Completed,
+            requested_amount: None,
+            multiplier: 5.0,
+            max_time: 14.0,
+        },
     );
 
     Ok(cleanup_dirs()?)
@@ -119,6 +173,12 @@ Max-Time:14"#
 /// verify_time
 #[test]
 fn sysd_fns_test() -> Result<(), DynError> {
+    let _sysd = SYSD.lock().unwrap_or_else(|e| e.into_inner());
+
+    // sysd_entry reads the profiles config
+    const PROF: &str = "sysd_test";
+    sysd_setup(PROF, "203")?;
+
     let bind = String::from(PROF);
     let not_now = { 
         let mut raw = Local::now()
@@ -143,7 +203,7 @@ fn sysd_fns_test() -> Result<(), DynError> {
 
     println!("{}", not_now);
 
-    sysd_entry(bind.clone(), 15.2, bind.clone(), not_now.clone())?; 
+    sysd_entry(bind.clone(), Dose::Time(15.2), bind.clone(), not_now.clone())?; 
 
     let (service_path, timer_path) = {
         let base = format!("{SYSTEMD_DIR}/{IDENTIFIER}_{PROF}");
@@ -158,6 +218,14 @@ fn sysd_fns_test() -> Result<(), DynError> {
         fs::read_to_string(&timer_path)?,
     );
 
+    // the limits are asserted by sysd_limits_test
+    let service_cont = service_cont
+        .lines()
+        .filter(|line| !line.starts_with("ExecStopPost=") && !line.starts_with("RuntimeMaxSec="))
+        .collect::<Vec<&str>>()
+        .join("\n")
+    ;
+
     assert_eq!(
         format!(
 r#"[Timer]
@@ -166,7 +234,7 @@ OnCalendar={not_now}
 WakeSystem=true
 Unit={IDENTIFIER}_{PROF}.service
 [Unit]
-Description=Times Actuations via gpio pins for the actuator program! 
+Description=Times actuations via systemd for gpio pins for the switch program.
 [Install]
 WantedBy=timers.target"#
         ),
@@ -176,13 +244,16 @@ WantedBy=timers.target"#
     assert_eq!(
         format!(
 r#"[Service]
-ExecStart=+/usr/local/bin/actuator --exec {PROF} 15.2
+ExecStart=+/usr/local/bin/switch --exec {PROF} 15.2
 [Unit]
-Description=Actuates gpio pins for the actautor program!"#
+Description=actuates gpio pins for the switch program"#
         ),
         service_cont,
     );
 
+    assert!(sysd_timers()?.contains(&(String::from(PROF), not_now.clone())));
+    assert_eq!(sysd_elapses(&not_now, 3)?.len(), 3, "a daily timer always elapses again");
+
     sysd_del_timer(bind)?;
     sysd_cleanup_unused_service()?;
 
@@ -213,5 +284,101 @@ Description=Actuates gpio pins for the actautor program!"#
         }
     }
 
-    Ok(())
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{PROF}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn sysd_limits_test() -> Result<(), DynError> {
+    let _sysd = SYSD.lock().unwrap_or_else(|e| e.into_inner());
+
+    const PROF: &str = "sysd_test_limits";
+    sysd_setup(PROF, "204")?;
+
+    let service_path = format!("{SYSTEMD_DIR}/{IDENTIFIER}_{PROF}.service");
+    let timer_path = |prefix: &str| format!("{SYSTEMD_DIR}/{IDENTIFIER}_{prefix}.timer");
+    let service = || -> Result<Vec<String>, DynError> {
+        Ok(fs::read_to_string(&service_path)?.lines().map(String::from).collect())
+    };
+
+    // a dose the profile can't actuate leaves nothing behind
+    assert!(sysd_entry(PROF.into(), Dose::Time(-1.0), "sysd_test_limits_a".into(), "06:00".into()).is_err());
+    assert!(!fs::exists(timer_path("sysd_test_limits_a"))?);
+    assert!(!fs::exists(&service_path)?);
+
+    sysd_entry(PROF.into(), Dose::Time(2.0), "sysd_test_limits_a".into(), "06:00".into())?;
+
+    let lines = service()?;
+    assert!(lines.contains(&format!("ExecStopPost=+/usr/local/bin/switch --force-off {PROF}")));
+    assert!(lines.contains(&String::from("RuntimeMaxSec=31")), "{lines:?}");
+
+    // the timers of the profile share the service, another dose
+    // would change what the first one actuates
+    assert!(sysd_entry(PROF.into(), Dose::Time(3.0), "sysd_test_limits_b".into(), "18:00".into()).is_err());
+    assert!(!fs::exists(timer_path("sysd_test_limits_b"))?);
+
+    // a resumed actuation runs before the new one
+    Recovery::set(String::from("resume"), PROF)?;
+    sysd_entry(PROF.into(), Dose::Time(2.0), "sysd_test_limits_b".into(), "18:00".into())?;
+    assert!(service()?.contains(&String::from("RuntimeMaxSec=51")));
+
+    // an entry can still change the dose of its own service
+    sysd_del_timer(String::from("sysd_test_limits_b"))?;
+    sysd_entry(PROF.into(), Dose::Time(3.0), "sysd_test_limits_a".into(), "06:00".into())?;
+    assert!(service()?.contains(&format!("ExecStart=+/usr/local/bin/switch --exec {PROF} 3")));
+
+    sysd_del_timer(String::from("sysd_test_limits_a"))?;
+    fs::remove_file(&service_path)?;
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{PROF}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn elapses_test() {
+    let out = "  Original form: 06,18:00
+Normalized form: *-*-* 06,18:00:00
+    Next elapse: Sun 2026-10-18 18:00:00 UTC
+       From now: 6h left
+       Iter. #2: Mon 2026-10-19 06:00:00 UTC
+       From now: 18h left";
+
+    assert_eq!(
+        elapses(out).iter().map(|time| time.to_string()).collect::<Vec<String>>(),
+        vec!["2026-10-18 18:00:00", "2026-10-19 06:00:00"],
+    );
+
+    assert!(elapses("    Next elapse: never").is_empty());
+}
+
+#[test]
+fn write_atomic_test() -> Result<(), DynError> {
+    let prof = "file_test_write_atomic";
+    init_dirs(prof)?;
+
+    let dir = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+    let path = format!("{dir}/log");
+
+    write_atomic(&path, "first")?;
+    write_atomic(&path, "second")?;
+
+    assert_eq!(fs::read_to_string(&path)?, "second");
+    assert_eq!(
+        fs::read_dir(&dir)?.count(),
+        1,
+        "the temporary file was left behind",
+    );
+
+    Ok(fs::remove_dir_all(dir)?)
 }
@@ -0,0 +1,45 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    file,
+    getter_setter::*,
+    gpio::sim::Sim,
+    unit::Dose,
+};
+
+use std::{
+    env,
+    fs,
+};
+
+/// a profile actuating a simulated pin, without a pin of its own
+/// (ex. to name a relay). Tests run in parallel, every test which
+/// touches the filesystem gets its own profile.
+pub fn profile(prof: &str) -> Result<(), DynError> {
+    file::init_dirs(prof)?;
+    GpioBackend::set(String::from("sim"), prof)
+}
+
+/// a profile actuating simulated pin 17, which executions of 
+/// other tests wait for under the default overlap policy.
+pub fn setup(prof: &str) -> Result<(), DynError> {
+    profile(prof)?;
+    GpioPin::set(String::from("17"), prof)
+}
+
+pub fn cleanup(prof: &str) -> Result<(), DynError> {
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+/// one short execution of the profile on a simulated pin.
+pub fn execute(prof: &str) -> Result<(), DynError> {
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    FullConfig::execute_with(config, Dose::Time(0.001), prof, &mut sim)
+}
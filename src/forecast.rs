@@ -0,0 +1,215 @@
+use crate::{
+    DynError,
+    file,
+    getter_setter::FullConfig,
+    record::{
+        self,
+        Event,
+        Record,
+    },
+    relay,
+};
+
+use std::{
+    fmt,
+    ops::Add,
+};
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+    Local,
+    NaiveDate,
+    TimeDelta,
+};
+
+#[cfg(test)]
+mod test;
+
+/// days of log the logged rate is worked out over.
+const WINDOW: f64 = 30.0;
+
+/// elapses of each timer asked of systemd, enough for a timer
+/// elapsing once a month to be averaged over years.
+const ITERATIONS: usize = 100;
+
+/// actuations per day, as logged lately and as scheduled by the
+/// timer entries. Either is None when there is nothing to go by.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Rate {
+    pub logged: Option<f64>,
+    pub scheduled: Option<f64>,
+}
+
+impl Rate {
+    /// the higher of the two, so a replacement relay is ordered
+    /// too early rather than too late.
+    pub fn per_day(&self) -> Option<f64> {
+        match (self.logged, self.scheduled) {
+            (Some(logged), Some(scheduled)) => Some(logged.max(scheduled)),
+            (logged, scheduled) => logged.or(scheduled),
+        }
+    }
+}
+
+/// profiles sharing a relay add up
+impl Add for Rate {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let sum = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+
+        Self {
+            logged: sum(self.logged, other.logged),
+            scheduled: sum(self.scheduled, other.scheduled),
+        }
+    }
+}
+
+/// when a relay is expected to reach its max actuations.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Forecast {
+    pub rate: Rate,
+    pub count: u64,
+    pub max: u64,
+}
+
+impl Forecast {
+    /// days until the relay is worn out at the current rate,
+    /// None if nothing is logged or scheduled.
+    pub fn days_left(&self) -> Option<f64> {
+        let per_day = self.rate.per_day().filter(|per_day| *per_day > 0.0)?;
+        Some(self.max.saturating_sub(self.count) as f64 / per_day)
+    }
+
+    /// the day the relay is expected to be worn out on.
+    pub fn date(&self, now: DateTime<Local>) -> Option<NaiveDate> {
+        let seconds = self.days_left()? * 86400.0;
+
+        // far enough out to be out of chronos range is as good as never
+        let left = TimeDelta::try_seconds(seconds as i64)?;
+        Some(now.checked_add_signed(left)?.date_naive())
+    }
+}
+
+impl fmt::Display for Forecast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let per_day = |rate: Option<f64>| rate
+            .map(|rate| format!("{rate:.1}"))
+            .unwrap_or(String::from("none"))
+        ;
+
+        let rate = format!(
+            "{} logged, {} scheduled",
+            per_day(self.rate.logged),
+            per_day(self.rate.scheduled),
+        );
+
+        if self.count >= self.max {
+            return write!(f, "Relay end of life: reached, see --replace-relay");
+        }
+
+        match (self.days_left(), self.date(Local::now())) {
+            (Some(days), Some(date)) => write!(
+                f,
+                "Relay end of life: around {date}, in {days:.0} days at {} actuations per day ({rate})",
+                per_day(self.rate.per_day()),
+            ),
+
+            _ => write!(f, "Relay end of life: unknown, no actuations per day to go by ({rate})"),
+        }
+    }
+}
+
+/// the forecast of the relay the profile drives. A relay shared
+/// through the relay registry is worn by every profile naming it.
+pub fn profile(config: &FullConfig, prof: &str) -> Result<Forecast, DynError> {
+    let rate = match &config.relay {
+        Some(name) => relay(name)?,
+        None => rate(prof)?,
+    };
+
+    Ok(Forecast {
+        rate,
+        count: config.relay_count,
+        max: config.relay_max,
+    })
+}
+
+/// the rates of every profile naming the registered relay 
+/// added up.
+pub fn relay(name: &str) -> Result<Rate, DynError> {
+    let mut sum = Rate::default();
+
+    for prof in relay::profiles(name)? {
+        sum = sum + rate(&prof)?;
+    }
+
+    Ok(sum)
+}
+
+/// the actuations per day of the profile, from its log and
+/// its timer entries.
+pub fn rate(prof: &str) -> Result<Rate, DynError> {
+    let logged = logged(&record::read(prof)?.records, Local::now().fixed_offset());
+
+    let mut scheduled = None;
+
+    for (timer_prof, calendar) in file::sysd_timers()? {
+        if timer_prof != prof {
+            continue;
+        }
+
+        let rate = per_day(&file::sysd_elapses(&calendar, ITERATIONS)?
+            .iter()
+            .map(|elapse| elapse.and_utc().timestamp())
+            .collect::<Vec<i64>>()
+        );
+
+        scheduled = Some(scheduled.unwrap_or(0.0) + rate);
+    }
+
+    Ok(Rate { logged, scheduled })
+}
+
+/// actuations per day over the last WINDOW days of the log, or
+/// since the first actuation logged if that was later (but at
+/// least a day). Interrupted actuations count as well. None if
+/// no actuation was ever logged.
+pub fn logged(records: &[Record], now: DateTime<FixedOffset>) -> Option<f64> {
+    let actuations = records
+        .iter()
+        .filter(|record| matches!(record.event, Event::Entry { .. } | Event::Interrupted { .. }))
+        .map(|record| record.timestamp)
+        .collect::<Vec<DateTime<FixedOffset>>>()
+    ;
+
+    let days = |timestamp: &DateTime<FixedOffset>| {
+        (now - *timestamp).num_seconds() as f64 / 86400.0
+    };
+
+    let first = actuations.iter().map(days).reduce(f64::max)?;
+
+    let recent = actuations
+        .iter()
+        .filter(|timestamp| days(timestamp) <= WINDOW)
+        .count()
+    ;
+
+    Some(recent as f64 / first.clamp(1.0, WINDOW))
+}
+
+/// elapses per day of a timer from its next elapses (unix
+/// seconds), a timer which elapses once or never adds nothing.
+fn per_day(elapses: &[i64]) -> f64 {
+    match (elapses.first(), elapses.last()) {
+        (Some(first), Some(last)) if last > first => {
+            (elapses.len() - 1) as f64 * 86400.0 / (last - first) as f64
+        }
+
+        _ => 0.0,
+    }
+}
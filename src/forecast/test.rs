@@ -0,0 +1,106 @@
+use crate::{
+    DynError,
+    forecast::*,
+    gpio::Outcome,
+    journal::Policy,
+    lock::OverlapPolicy,
+    record::Event,
+    unit::Dose,
+};
+
+use chrono::{
+    DateTime,
+    Local,
+    TimeDelta,
+};
+
+/// a record of event days before now
+fn record(event: Event, now: DateTime<FixedOffset>, days: i64) -> Record {
+    Record {
+        profile: String::from("forecast_test"),
+        pin: 17,
+        timestamp: now - TimeDelta::days(days),
+        event,
+    }
+}
+
+fn entry() -> Event {
+    Event::Entry {
+        requested_seconds: 1.0,
+        actual_seconds: 1.0,
+        clamped: false,
+        outcome: Outcome::Completed,
+        requested_amount: None,
+        multiplier: 1.0,
+        max_time: 30.0,
+    }
+}
+
+#[test]
+fn logged_test() -> Result<(), DynError> {
+    let now = DateTime::parse_from_rfc3339("2026-10-18T12:00:00+02:00")?;
+
+    assert_eq!(logged(&[], now), None);
+
+    // 4 actuations over the 10 days since the first one, the
+    // skipped execution and safe state did not actuate
+    let interrupted = Event::Interrupted {
+        requested_seconds: 1.0,
+        actual_seconds: 0.5,
+        started: now,
+        recovery: Policy::Log,
+    };
+
+    let log = [
+        record(entry(), now, 10),
+        record(Event::Skipped { requested: Dose::Time(1.0), overlap: OverlapPolicy::Skip }, now, 9),
+        record(interrupted, now, 6),
+        record(Event::SafeState { previous: Outcome::Interrupted }, now, 6),
+        record(entry(), now, 2),
+        record(entry(), now, 0),
+    ];
+    assert_eq!(logged(&log, now), Some(0.4));
+
+    // only the last 30 days count, over all 30 of them
+    let log = [
+        record(entry(), now, 90),
+        record(entry(), now, 29),
+        record(entry(), now, 1),
+        record(entry(), now, 1),
+    ];
+    assert_eq!(logged(&log, now), Some(0.1));
+
+    // a first day is taken as a whole one
+    assert_eq!(logged(&[record(entry(), now, 0)], now), Some(1.0));
+
+    Ok(())
+}
+
+#[test]
+fn per_day_test() {
+    // twice a day, 3 elapses over a day
+    assert_eq!(per_day(&[0, 43200, 86400]), 2.0);
+    assert_eq!(per_day(&[0]), 0.0);
+    assert_eq!(per_day(&[]), 0.0);
+}
+
+#[test]
+fn forecast_test() {
+    let rate = Rate { logged: Some(4.0), scheduled: None }
+        + Rate { logged: Some(1.0), scheduled: Some(10.0) };
+    assert_eq!(rate, Rate { logged: Some(5.0), scheduled: Some(10.0) });
+    assert_eq!(rate.per_day(), Some(10.0));
+
+    let forecast = Forecast { rate, count: 700, max: 1000 };
+    assert_eq!(forecast.days_left(), Some(30.0));
+
+    let now = Local::now();
+    assert_eq!(forecast.date(now), Some((now + TimeDelta::days(30)).date_naive()));
+
+    let unknown = Forecast { rate: Rate::default(), count: 700, max: 1000 };
+    assert_eq!(unknown.days_left(), None);
+    assert!(unknown.to_string().contains("unknown"));
+
+    let idle = Forecast { rate: Rate { logged: Some(0.0), scheduled: None }, ..forecast };
+    assert_eq!(idle.date(now), None);
+}
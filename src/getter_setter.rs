@@ -1,157 +1,599 @@
 use crate::{
     DIR_NAME,
-    errors::MalformedConfigError,
+    calibrate::{
+        Calibration,
+        Curve,
+        Trial,
+    },
+    errors::{
+        InvalidConfigError,
+        InvalidCountError,
+        InvalidCurveError,
+        InvalidFlowRateError,
+        InvalidMaxTimeError,
+        InvalidThresholdError,
+        MalformedConfigError,
+        ProfileExistsError,
+        RelayPinMismatchError,
+    },
+    gpio::{
+        BackendKind,
+        ActiveLevel,
+    },
+    expr,
+    file,
+    journal::Policy,
+    lock::{
+        OverlapPolicy,
+        WriteLock,
+    },
+    relay::{
+        self,
+        Retired,
+    },
+    unit::VolumeUnit,
+    wear::{
+        self,
+        WearPolicy,
+    },
     DynError,
 };
 
 use std::{
-    str::Lines,
     env,
+    fmt::Display,
     fs,
-    str,
+    str::{
+        self,
+        FromStr,
+    },
 };
 
 #[cfg(test)]
 mod test;
 
+pub mod legacy;
+pub mod schema;
+
 use chrono_tz::Tz;
 
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+};
+
+use toml_edit::{
+    ArrayOfTables,
+    Datetime,
+    DocumentMut,
+    Item,
+    Value,
+};
+
 #[derive(Debug, PartialEq)]
 pub struct FullConfig { 
+    /// the registered relay the profile drives, if any. Its count,
+    /// max and pin are used in place of the profiles own.
+    pub relay: Option<String>,
     pub relay_count: u64,
     pub relay_max: u64,                           
+    /// when the relay was put in, known once it was added with 
+    /// --add-relay or replaced with --replace-relay
+    pub relay_installed: Option<Datetime>,
+    /// the relays it replaced, see --replace-relay
+    pub relay_history: Vec<Retired>,
+    pub warn_at: Vec<f64>,
+    pub wear: WearPolicy,
     pub max_time: f64,                          
     pub gpio_pin: u8,                             
     pub timezone: chrono_tz::Tz,                 
     pub multiplier: f64,                         
+    pub backend: BackendKind,
+    pub gpio_chip: String,
+    pub gpio_line: u32,
+    pub polarity: ActiveLevel,
+    pub recovery: Policy,
+    pub overlap: OverlapPolicy,
+    pub unit: VolumeUnit,
+    pub flow_rate: Option<f64>,
+    pub curve: Option<Curve>,
 }
 
 impl FullConfig {
+    /// reads the profiles toml config, older configs (including
+    /// legacy "Key: value" ones) are upgraded in place first. A
+    /// profile naming a relay reads its count, max and pin from 
+    /// the relay registry.
     pub fn new(prof_id: &str) -> Result<Self, DynError> {
-        let path = {
-            let var = env::var("XDG_CONFIG_HOME")?;
-            format!("{var}/{DIR_NAME}/{prof_id}/config")
-        };
+        // the toml error names the key and shows its line
+        let profile = toml::from_str::<Profile>(&read(prof_id)?)
+            .map_err(|e| InvalidConfigError::new(e.to_string()))?
+        ;
 
-        let config_contents = fs::read_to_string(&path)?;
+        let (relay_count, relay_max, gpio_pin, relay_installed, relay_history) = match &profile.relay.name {
+            Some(name) => {
+                let relay = relay::get(name)?;
 
-        if config_contents.is_empty() {
-            return Err(MalformedConfigError::new().into()); 
-        }
+                if let Some(pin) = profile.backend.pin.filter(|pin| *pin != relay.pin) {
+                    return Err(RelayPinMismatchError::new(pin, relay.pin).into());
+                }
 
-        let mut cont_lines = config_contents.lines();
+                (relay.count, relay.max, relay.pin, relay.installed, relay.history)
+            }
+
+            None => (
+                profile.relay.count,
+                profile.relay.max,
+                profile.backend.pin.ok_or(InvalidConfigError::new(String::from(
+                    "missing field `pin` in [backend], set it with --set-gpio or name a relay with --set-relay"
+                )))?,
+                profile.relay.installed,
+                profile.relay.history,
+            ),
+        };
 
         Ok(FullConfig {
-            relay_count: RelayCount::get(&mut cont_lines)?.0,
-            relay_max: RelayMax::get(&mut cont_lines)?.0,
-            max_time: MaxTime::get(&mut cont_lines)?.0,
-            gpio_pin: GpioPin::get(&mut cont_lines)?.0,
-            timezone: Timezone::get(&mut cont_lines)?.0,
-            multiplier: Multiplier::get(&mut cont_lines)?.0,
+            relay: profile.relay.name,
+            relay_count,
+            relay_max,
+            relay_installed,
+            relay_history,
+            warn_at: profile.relay.warn_at,
+            wear: profile.relay.worn,
+            max_time: profile.safety.max_time,
+            gpio_pin,
+            timezone: profile.timezone,
+            multiplier: profile.calibration.multiplier,
+            backend: profile.backend.kind,
+            gpio_chip: profile.backend.chip,
+            // the line offset defaults to the pin number, on a 
+            // Raspberry Pi gpiochip0 line N is BCM gpio N.
+            gpio_line: profile
+                .backend
+                .line
+                .unwrap_or(gpio_pin as u32),
+            polarity: profile.relay.polarity,
+            recovery: profile.safety.recovery,
+            overlap: profile.safety.overlap,
+            unit: profile.dosing.unit,
+            flow_rate: profile.dosing.flow_rate,
+            curve: profile.calibration.curve,
         })
     }
 }
 
-pub trait Config {
-    const RAW: &'static str = 
-r#"RelayCount:
-RelayMax:
-MaxTime:
-GpioPin:
-Timezone:
-Multiplier:"# 
-    ;
+/// the layout of a profiles config file. Every key but 
+/// backend.pin has a default, TEMPLATE documents them. 
+/// backend.pin can be left out when relay.name is set.
+#[derive(Debug, Deserialize)]
+struct Profile {
+    #[serde(default = "utc", deserialize_with = "timezone")]
+    timezone: Tz,
+    #[serde(default)]
+    relay: RelaySection,
+    backend: BackendSection,
+    #[serde(default)]
+    safety: SafetySection,
+    #[serde(default)]
+    calibration: CalibrationSection,
+    #[serde(default)]
+    dosing: DosingSection,
+}
 
-    const LINE: usize;
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RelaySection {
+    name: Option<String>,
+    count: u64,
+    max: u64,
+    installed: Option<Datetime>,
+    history: Vec<Retired>,
+    #[serde(deserialize_with = "thresholds")]
+    warn_at: Vec<f64>,
+    #[serde(deserialize_with = "parsed")]
+    worn: WearPolicy,
+    #[serde(deserialize_with = "parsed")]
+    polarity: ActiveLevel,
+}
 
-    fn verifier(set: &String) -> Result<(), DynError>;
+impl Default for RelaySection {
+    fn default() -> Self {
+        Self {
+            name: None,
+            count: 0,
+            max: 100000,
+            installed: None,
+            history: Vec::new(),
+            warn_at: wear::WARN_AT.to_vec(),
+            worn: WearPolicy::default(),
+            polarity: ActiveLevel::default(),
+        }
+    }
+}
 
-    fn set(
-        set: String,
-        id: &str,
-    ) -> Result<(), DynError> {
-        Self::verifier(&set)?;
+#[derive(Debug, Deserialize)]
+struct BackendSection {
+    #[serde(default, deserialize_with = "parsed")]
+    kind: BackendKind,
+    pin: Option<u8>,
+    #[serde(default = "gpiochip0", deserialize_with = "chip")]
+    chip: String,
+    line: Option<u32>,
+}
 
-        let line_number = Self::LINE;
-        let path = {
-            let var = env::var("XDG_CONFIG_HOME")?;
-            format!("/{var}/{DIR_NAME}/{id}/config")
-        };
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct SafetySection {
+    max_time: f64,
+    #[serde(deserialize_with = "parsed")]
+    recovery: Policy,
+    #[serde(deserialize_with = "parsed")]
+    overlap: OverlapPolicy,
+}
 
-        if !fs::exists(&path)? {
-            let mut lines = Self::RAW
-                .lines()
-                .collect::<Vec<&str>>()
-            ;
+impl Default for SafetySection {
+    fn default() -> Self {
+        Self {
+            max_time: 30.0,
+            recovery: Policy::default(),
+            overlap: OverlapPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct CalibrationSection {
+    #[serde(deserialize_with = "expression")]
+    multiplier: f64,
+    #[serde(deserialize_with = "curve")]
+    curve: Option<Curve>,
+}
 
-            let split = lines[line_number]
-                .split(':')
-                .collect::<Vec<&str>>()
-            ; 
+impl Default for CalibrationSection {
+    fn default() -> Self {
+        Self { multiplier: 1.0, curve: None }
+    }
+}
 
-            let bind = format!("{}: {}", split[0], set);
-            lines[line_number] = bind.as_str();
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DosingSection {
+    #[serde(deserialize_with = "parsed")]
+    unit: VolumeUnit,
+    #[serde(deserialize_with = "some_expression")]
+    flow_rate: Option<f64>,
+}
 
-            return Ok(fs::write(
-                    &path,
-                    lines.join("\n"))?,
-            )
-        } else {
-            let config = fs::read_to_string(&path)?;
+fn utc() -> Tz {
+    Tz::UTC
+}
 
-            let mut lines = config
-                .lines()
-                .collect::<Vec<&str>>()
-            ;
+fn gpiochip0() -> String {
+    String::from("/dev/gpiochip0")
+}
+
+fn timezone<'de, D: Deserializer<'de>>(de: D) -> Result<Tz, D::Error> {
+    let raw = String::deserialize(de)?;
+    Timezone::parser(&raw)
+        .map(|tz| tz.0)
+        .map_err(de::Error::custom)
+}
+
+/// a number or a string holding an expression for expr::eval
+fn expression<'de, D: Deserializer<'de>>(de: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Expression(String),
+    }
+
+    match Raw::deserialize(de)? {
+        Raw::Number(number) => Ok(number),
+        Raw::Expression(raw) => expr::eval(&raw).map_err(de::Error::custom),
+    }
+}
+
+fn some_expression<'de, D: Deserializer<'de>>(de: D) -> Result<Option<f64>, D::Error> {
+    expression(de).map(Some)
+}
+
+fn curve<'de, D: Deserializer<'de>>(de: D) -> Result<Option<Curve>, D::Error> {
+    let points = Vec::<Trial>::deserialize(de)?;
+    Curve::new(points).map(Some).map_err(de::Error::custom)
+}
+
+fn thresholds<'de, D: Deserializer<'de>>(de: D) -> Result<Vec<f64>, D::Error> {
+    let thresholds = Vec::<f64>::deserialize(de)?;
+    WarnAt::check(&thresholds).map_err(de::Error::custom)?;
+    Ok(thresholds)
+}
+
+fn chip<'de, D: Deserializer<'de>>(de: D) -> Result<String, D::Error> {
+    let raw = String::deserialize(de)?;
+    GpioChip::parser(&raw)
+        .map(|chip| chip.0)
+        .map_err(de::Error::custom)
+}
+
+/// enums are written with their Display form, ex. active-low
+fn parsed<'de, D, T>(de: D) -> Result<T, D::Error> 
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let raw = String::deserialize(de)?;
+    raw.parse::<T>().map_err(de::Error::custom)
+}
+
+/// a complete config with every key described and set to its 
+/// default. backend.pin has no default and is left commented,
+/// so are backend.line which defaults to the pin and 
+/// calibration.curve and dosing.flow_rate which only dosing by 
+/// amount needs.
+pub const TEMPLATE: &str = 
+r#"# layout version of this file, upgraded by the program.
+version = 1
+
+# IANA timezone used for log timestamps and entries.
+timezone = "UTC"
+
+[relay]
+# a relay added with --add-relay which this profile drives. Its pin,
+# count and max are used in place of backend.pin, count and max below,
+# so profiles sharing a relay wear it as one.
+# name = "pump"
+# actuations counted so far, compared against max.
+count = 0
+# actuations the relay is rated for.
+max = 100000
+# when the relay was put in, --replace-relay sets it and moves the
+# count, max and install date of the relay it replaces to history.
+# installed = 2025-01-01T00:00:00+00:00
+# percentages of max at which a warning is printed and logged.
+warn_at = [80.0, 95.0]
+# refuse, override or warn, what happens once count reaches max;
+# override refuses unless --override-wear is given.
+worn = "override"
+# active-high or active-low, the level which energizes the relay.
+polarity = "active-high"
+
+[backend]
+# rppal (Raspberry Pi), cdev (/dev/gpiochipN) or sim (no hardware).
+kind = "rppal"
+# BCM number of the gpio pin driving the relay, has to be set
+# unless relay.name is.
+# pin = 17
+# gpio character device used by the cdev backend.
+chip = "/dev/gpiochip0"
+# line offset on chip used by the cdev backend, defaults to pin.
+# line = 17
+
+[safety]
+# longest a single actuation may last, in seconds.
+max_time = 30.0
+# log, resume or refuse, what happens after an unfinished actuation.
+recovery = "log"
+# wait, skip or fail, what happens while another execution holds the pin.
+overlap = "wait"
+
+[calibration]
+# the time actuated for is multiplied by this, either a number or an
+# expression in quotes, ex. "(1/0.0338)*1.25" or "1/8floz". --calibrate
+# sets it and keeps its trials below, as [[calibration.history]].
+multiplier = 1.0
+# amounts (in dosing.unit) put out after so many seconds. When set,
+# amounts are turned into seconds by interpolating between these 
+# points instead of dividing by dosing.flow_rate, for pumps and valves
+# with start-up lag.
+# curve = [{ seconds = 2.0, amount = 5.0 }, { seconds = 10.0, amount = 40.0 }]
+
+[dosing]
+# ml, l, floz or gal, the unit of the flow rate and of amounts
+# given to --dispense without one.
+unit = "ml"
+# units the relay lets through per second, either a number or an
+# expression in quotes, has to be set to actuate by amount.
+# flow_rate = 12.5
+"#;
+
+fn config_path(id: &str) -> Result<String, DynError> {
+    let var = env::var("XDG_CONFIG_HOME")?;
+    Ok(format!("{var}/{DIR_NAME}/{id}/config"))
+}
+
+/// the profiles config, upgraded to the current version first.
+fn read(id: &str) -> Result<String, DynError> {
+    let _lock = WriteLock::acquire(id)?;
+    read_locked(id)
+}
+
+/// read for callers which already hold the profiles WriteLock.
+fn read_locked(id: &str) -> Result<String, DynError> {
+    let path = config_path(id)?;
+    let raw = fs::read_to_string(&path)?;
+
+    Ok(schema::upgrade(&raw, &path)?.0)
+}
+
+/// upgrades the profiles config to the current version,
+/// returns the version it was at.
+pub fn migrate(id: &str) -> Result<i64, DynError> {
+    let _lock = WriteLock::acquire(id)?;
+
+    let path = config_path(id)?;
+    let raw = fs::read_to_string(&path)?;
+
+    Ok(schema::upgrade(&raw, &path)?.1)
+}
+
+/// writes the template config for a new profile, 
+/// an existing config is never overwritten.
+pub fn init_profile(id: &str) -> Result<(), DynError> {
+    let _lock = WriteLock::acquire(id)?;
+
+    let path = config_path(id)?;
+
+    if fs::exists(&path)? {
+        return Err(ProfileExistsError::new().into());
+    }
+
+    file::write_atomic(&path, TEMPLATE)
+}
+
+/// applies edit to the profiles config and writes it back while
+/// holding the profiles WriteLock, so no other setter or execution
+/// can change the config in between. A profile without a config 
+/// starts from TEMPLATE.
+fn update(
+    id: &str,
+    edit: impl FnOnce(&mut DocumentMut) -> Result<(), DynError>,
+) -> Result<(), DynError> {
+    let _lock = WriteLock::acquire(id)?;
+
+    let path = config_path(id)?;
 
-            let split = lines[line_number]
-                .split(':')
-                .collect::<Vec<&str>>()
-            ; 
+    let mut doc = if fs::exists(&path)? {
+        read_locked(id)?.parse::<DocumentMut>()?
+    } else {
+        TEMPLATE.parse::<DocumentMut>()?
+    };
 
-            let bind = format!("{}: {}", split[0], set);
-            lines[line_number] = bind.as_str();
+    edit(&mut doc)?;
 
-            Ok(fs::write(&path, lines.join("\n"))?) 
+    file::write_atomic(&path, &doc.to_string())
+}
+
+/// sets the value at a dotted key (ex. relay.count), creating 
+/// its section if needed. The comments around an existing value 
+/// are kept.
+fn set_value(
+    doc: &mut DocumentMut,
+    key: &str,
+    value: Value,
+) -> Result<(), DynError> {
+    let (table, key) = match key.split_once('.') {
+        Some((section, key)) => (
+            doc
+                .entry(section)
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or(MalformedConfigError::new())?,
+            key,
+        ),
+        None => (doc.as_table_mut(), key),
+    };
+
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(old) => {
+            let decor = old.decor().clone();
+            *old = value;
+            *old.decor_mut() = decor;
+        }
+
+        None => {
+            table.insert(key, Item::Value(value));
         }
     }
 
+    Ok(())
+}
+
+/// the whole number at key (ex. relay.count), None if it isn't 
+/// set. A value which isn't a whole number is an error rather
+/// than read as unset, the count of a relay is never lost.
+pub fn whole_number(item: Option<&Item>, key: &str) -> Result<Option<u64>, DynError> {
+    let Some(item) = item else {
+        return Ok(None);
+    };
+
+    item
+        .as_integer()
+        .and_then(|number| u64::try_from(number).ok())
+        .map(Some)
+        .ok_or(InvalidCountError::new(key, item.to_string().trim().to_string()).into())
+}
+
+pub trait Config {
+    /// where the key is in the profile, ex. relay.count
+    const KEY: &'static str;
+
+    /// the name of the key in the legacy "Key: value" config, 
+    /// None for keys added since profiles moved to toml
+    const LEGACY: Option<&'static str> = None;
+
+    fn verifier(set: &String) -> Result<(), DynError>;
 
     fn parser(got: &str) -> Result<Self, DynError> where Self: Sized;
 
-    /// this function needs to be implemented in the get_all fn 
-    /// in order of LINE's otherwise it will not work.
-    #[inline]
-    fn get(config_lines: &mut Lines) -> Result<Self, DynError> 
+    /// the value as it is written to the profile
+    fn value(self) -> Result<Value, DynError>;
+
+    /// rewrites KEY in the profile, a profile without a config
+    /// starts from TEMPLATE; every other key and comment is left
+    /// untouched.
+    fn set(
+        set: String,
+        id: &str,
+    ) -> Result<(), DynError> 
     where 
         Self: Sized,
     {
-        let raw = config_lines
-            .next()
-            .ok_or(MalformedConfigError::new())?
-        ;
-
-        let val = {
-            let split = raw
-                .split(' ') 
-                .collect::<Vec<&str>>()
-            ; 
-
-            if split.len() == 2 {
-                Ok(split[1])
-            } else {
-                Err(MalformedConfigError::new())
-            }
-        }?;
+        Self::verifier(&set)?;
+        let value = Self::parser(&set)?.value()?;
 
-        Ok(Self::parser(val)?)
+        update(id, |doc| set_value(doc, Self::KEY, value))
     }
 }
 
 pub struct RelayCount(u64);
 
+impl RelayCount {
+    /// adds one actuation to the count in the config file, 
+    /// returns the new count.
+    pub fn increment(id: &str) -> Result<u64, DynError> {
+        let mut count = 0;
+
+        update(id, |doc| {
+            let current = whole_number(
+                doc.get("relay").and_then(|relay| relay.get("count")), 
+                Self::KEY,
+            )?;
+
+            count = current.unwrap_or(0) + 1;
+            set_value(doc, Self::KEY, Self(count).value()?)
+        })?;
+
+        Ok(count)
+    }
+
+    /// retires the relay of the profile and starts counting its 
+    /// replacement from 0, see relay::retire.
+    pub fn replace(id: &str, max: Option<u64>) -> Result<Retired, DynError> {
+        let mut retired = None;
+
+        update(id, |doc| {
+            let relay = doc
+                .entry("relay")
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or(MalformedConfigError::new())?
+            ;
+
+            retired = Some(relay::retire(relay, RelaySection::default().max, max)?);
+            Ok(())
+        })?;
+
+        Ok(retired.ok_or(MalformedConfigError::new())?)
+    }
+}
+
 impl Config for RelayCount {
-    const LINE: usize = 0;
+    const KEY: &'static str = "relay.count";
+    const LEGACY: Option<&'static str> = Some("RelayCount");
 
     fn parser(got: &str) -> Result<Self, DynError> {
         Ok(Self(got.parse::<u64>()?))
@@ -161,12 +603,70 @@ impl Config for RelayCount {
         set.parse::<u64>()?;
         Ok(())
     }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(i64::try_from(self.0)?))
+    }
+
+    /// the count of a profile naming a relay is kept by the relay
+    /// registry, its own relay.count is never read.
+    fn set(set: String, id: &str) -> Result<(), DynError> {
+        Self::verifier(&set)?;
+        let count = Self::parser(&set)?;
+
+        match RelayName::get(id)? {
+            Some(name) => relay::set_count(&name, count.0),
+            None => update(id, |doc| set_value(doc, Self::KEY, count.value()?)),
+        }
+    }
+}
+
+/// the registered relay the profile drives, see relay::Relay.
+pub struct RelayName(String);
+
+impl RelayName {
+    /// the relay the profile names, None if it names none or 
+    /// has no config yet.
+    pub fn get(id: &str) -> Result<Option<String>, DynError> {
+        if !fs::exists(config_path(id)?)? {
+            return Ok(None);
+        }
+
+        Ok(read(id)?
+            .parse::<DocumentMut>()?
+            .get("relay")
+            .and_then(|relay| relay.get("name"))
+            .and_then(Item::as_str)
+            .map(String::from)
+        )
+    }
+}
+
+impl Config for RelayName {
+    const KEY: &'static str = "relay.name";
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Self::verifier(&got.to_string())?;
+        Ok(Self(got.to_string()))
+    }
+
+    /// the relay has to be registered
+    fn verifier(set: &String) -> Result<(), DynError> {
+        relay::verify_name(set)?;
+        relay::get(set)?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0))
+    }
 }
 
 pub struct RelayMax(u64);           
 
 impl Config for RelayMax {
-    const LINE: usize = 1;
+    const KEY: &'static str = "relay.max";
+    const LEGACY: Option<&'static str> = Some("RelayMax");
 
     fn parser(got: &str) -> Result<Self, DynError> {
         Ok(Self(got.parse::<u64>()?))
@@ -176,27 +676,127 @@ impl Config for RelayMax {
         set.parse::<u64>()?;
         Ok(())
     }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(i64::try_from(self.0)?))
+    }
+
+    /// same as RelayCount::set, a relay in the registry keeps 
+    /// its own max.
+    fn set(set: String, id: &str) -> Result<(), DynError> {
+        Self::verifier(&set)?;
+        let max = Self::parser(&set)?;
+
+        match RelayName::get(id)? {
+            Some(name) => relay::set_max(&name, max.0),
+            None => update(id, |doc| set_value(doc, Self::KEY, max.value()?)),
+        }
+    }
+}
+
+/// percentages of RelayMax at which a warning is printed 
+/// and logged, written as 80,95.
+pub struct WarnAt(Vec<f64>);
+
+impl WarnAt {
+    fn check(thresholds: &[f64]) -> Result<(), InvalidThresholdError> {
+        match thresholds
+            .iter()
+            .all(|threshold| *threshold > 0.0 && *threshold <= 100.0)
+        {
+            true => Ok(()),
+            false => Err(InvalidThresholdError::new()),
+        }
+    }
+}
+
+impl Config for WarnAt {
+    const KEY: &'static str = "relay.warn_at";
+
+    /// an empty string turns the warnings off
+    fn parser(got: &str) -> Result<Self, DynError> {
+        let mut thresholds = Vec::new();
+
+        for threshold in got.split(',').filter(|t| !t.trim().is_empty()) {
+            thresholds.push(
+                threshold
+                    .trim()
+                    .trim_end_matches('%')
+                    .parse::<f64>()
+                    .map_err(|_| InvalidThresholdError::new())?
+            );
+        }
+
+        Self::check(&thresholds)?;
+        thresholds.sort_by(f64::total_cmp);
+
+        Ok(Self(thresholds))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        Self::parser(set)?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::Array(self.0.into_iter().collect()))
+    }
+}
+
+/// what happens once RelayCount reaches RelayMax,
+/// see wear::WearPolicy.
+pub struct Wear(WearPolicy);
+
+impl Config for Wear {
+    const KEY: &'static str = "relay.worn";
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<WearPolicy>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<WearPolicy>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.to_string()))
+    }
 }
 
 pub struct MaxTime(f64);          
 
 impl Config for MaxTime {
-    const LINE: usize = 2;
+    const KEY: &'static str = "safety.max_time";
+    const LEGACY: Option<&'static str> = Some("MaxTime");
 
     fn parser(got: &str) -> Result<Self, DynError> {
+        Self::verifier(&got.to_string())?;
         Ok(Self(got.parse::<f64>()?))
     }
 
+    /// the max time caps every actuation, so it has 
+    /// to be a usable number of seconds
     fn verifier(set: &String) -> Result<(), DynError> {
-        set.parse::<f64>()?;
+        let max_time = set.parse::<f64>()?;
+
+        if !max_time.is_finite() || max_time < 0.0 {
+            return Err(InvalidMaxTimeError::new().into());
+        }
+
         Ok(())
     }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0))
+    }
 }
 
 pub struct GpioPin(u8);             
 
 impl Config for GpioPin {
-    const LINE: usize = 3;
+    const KEY: &'static str = "backend.pin";
+    const LEGACY: Option<&'static str> = Some("GpioPin");
 
     fn parser(got: &str) -> Result<Self, DynError> {
         Ok(Self(got.parse::<u8>()?))
@@ -206,12 +806,39 @@ impl Config for GpioPin {
         set.parse::<u8>()?;
         Ok(())
     }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(i64::from(self.0)))
+    }
+}
+
+/// whether the relay on the pin is energized by driving 
+/// the pin high (active-high) or low (active-low).
+pub struct Polarity(ActiveLevel);
+
+impl Config for Polarity {
+    const KEY: &'static str = "relay.polarity";
+    const LEGACY: Option<&'static str> = Some("Polarity");
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<ActiveLevel>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<ActiveLevel>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.to_string()))
+    }
 }
 
 pub struct Timezone(chrono_tz::Tz); 
 
 impl Config for Timezone {
-    const LINE: usize = 4;
+    const KEY: &'static str = "timezone";
+    const LEGACY: Option<&'static str> = Some("Timezone");
 
     fn parser(got: &str) -> Result<Self, DynError> {
         Ok(Self(Tz::from_str_insensitive(got)?))
@@ -220,20 +847,271 @@ impl Config for Timezone {
     fn verifier(set: &String) -> Result<(), DynError> {
         Tz::from_str_insensitive(set)?;
         Ok(())
-    }   
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.name()))
+    }
 }
 
-pub struct Multiplier(f64);         
+/// an expression (see expr::eval) kept as it was written, 
+/// plain numbers are written to the profile as numbers.
+pub struct Multiplier(String);
 
 impl Config for Multiplier {
-    const LINE: usize = 5;
+    const KEY: &'static str = "calibration.multiplier";
+    const LEGACY: Option<&'static str> = Some("Multiplier");
 
     fn parser(got: &str) -> Result<Self, DynError> {
-        Ok(Self(got.parse::<f64>()?))
+        Self::verifier(&got.to_string())?;
+        Ok(Self(got.trim().to_string()))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        expr::eval(set)?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(match self.0.parse::<f64>() {
+            Ok(number) => Value::from(number),
+            Err(_) => Value::from(self.0),
+        })
+    }
+}
+
+impl Multiplier {
+    /// sets the multiplier and flow rate worked out by --calibrate
+    /// and appends the calibration to calibration.history, earlier
+    /// ones are kept.
+    pub fn calibrate(
+        calibration: &Calibration,
+        id: &str,
+    ) -> Result<(), DynError> {
+        let entry = calibration.table()?;
+
+        update(id, |doc| {
+            set_value(doc, Self::KEY, Value::from(calibration.multiplier()))?;
+            set_value(doc, FlowRate::KEY, Value::from(calibration.flow_rate()))?;
+
+            doc
+                .entry("calibration")
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or(MalformedConfigError::new())?
+                .entry("history")
+                .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+                .as_array_of_tables_mut()
+                .ok_or(MalformedConfigError::new())?
+                .push(entry)
+            ;
+
+            Ok(())
+        })
+    }
+}
+
+/// the calibration table of the profile, see calibrate::Curve.
+/// Written as seconds:amount points, ex. 2:5,10:40.
+pub struct CalibrationCurve(Curve);
+
+impl Config for CalibrationCurve {
+    const KEY: &'static str = "calibration.curve";
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        let mut points = Vec::new();
+
+        for point in got.split(',') {
+            let (seconds, amount) = point
+                .split_once(':')
+                .ok_or(InvalidCurveError::new(format!("{} is not seconds:amount", point.trim())))?
+            ;
+
+            points.push(Trial {
+                seconds: seconds.trim().parse::<f64>()?,
+                amount: amount.trim().parse::<f64>()?,
+            });
+        }
+
+        Ok(Self(Curve::new(points)?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        Self::parser(set)?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::Array(
+            self.0
+                .points()
+                .iter()
+                .map(Trial::inline)
+                .collect()
+        ))
+    }
+}
+
+pub struct GpioBackend(BackendKind);
+
+impl Config for GpioBackend {
+    const KEY: &'static str = "backend.kind";
+    const LEGACY: Option<&'static str> = Some("GpioBackend");
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<BackendKind>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<BackendKind>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.to_string()))
+    }
+}
+
+pub struct GpioChip(String);
+
+impl Config for GpioChip {
+    const KEY: &'static str = "backend.chip";
+    const LEGACY: Option<&'static str> = Some("GpioChip");
+
+    /// accepts both the bare chip name and the device path
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Self::verifier(&got.to_string())?;
+
+        Ok(Self(if got.starts_with('/') {
+            got.to_string()
+        } else {
+            format!("/dev/{got}")
+        }))
     }
 
     fn verifier(set: &String) -> Result<(), DynError> {
-        set.parse::<f64>()?;
+        if set.is_empty() || set.contains(char::is_whitespace) {
+            return Err(MalformedConfigError::new().into());
+        }
+
         Ok(())
     }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0))
+    }
+}
+
+pub struct GpioLine(u32);
+
+impl Config for GpioLine {
+    const KEY: &'static str = "backend.line";
+    const LEGACY: Option<&'static str> = Some("GpioLine");
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<u32>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<u32>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(i64::from(self.0)))
+    }
+}
+
+/// what the next execution does after an actuation 
+/// was left unfinished, see journal::Policy.
+pub struct Recovery(Policy);
+
+impl Config for Recovery {
+    const KEY: &'static str = "safety.recovery";
+    const LEGACY: Option<&'static str> = Some("Recovery");
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<Policy>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<Policy>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.to_string()))
+    }
+}
+
+/// what an execution does while another one holds the profile 
+/// or its pin, see lock::OverlapPolicy.
+pub struct Overlap(OverlapPolicy);
+
+impl Config for Overlap {
+    const KEY: &'static str = "safety.overlap";
+    const LEGACY: Option<&'static str> = Some("Overlap");
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<OverlapPolicy>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<OverlapPolicy>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.to_string()))
+    }
+}
+
+/// the unit a profile doses in, see unit::VolumeUnit.
+pub struct DoseUnit(VolumeUnit);
+
+impl Config for DoseUnit {
+    const KEY: &'static str = "dosing.unit";
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Ok(Self(got.parse::<VolumeUnit>()?))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        set.parse::<VolumeUnit>()?;
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(Value::from(self.0.to_string()))
+    }
+}
+
+/// units of the profiles DoseUnit dispensed per second, an 
+/// expression like Multiplier which has to be above 0.
+pub struct FlowRate(String);
+
+impl Config for FlowRate {
+    const KEY: &'static str = "dosing.flow_rate";
+
+    fn parser(got: &str) -> Result<Self, DynError> {
+        Self::verifier(&got.to_string())?;
+        Ok(Self(got.trim().to_string()))
+    }
+
+    fn verifier(set: &String) -> Result<(), DynError> {
+        let rate = expr::eval(set)?;
+
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(InvalidFlowRateError::new().into());
+        }
+
+        Ok(())
+    }
+
+    fn value(self) -> Result<Value, DynError> {
+        Ok(match self.0.parse::<f64>() {
+            Ok(number) => Value::from(number),
+            Err(_) => Value::from(self.0),
+        })
+    }
 }
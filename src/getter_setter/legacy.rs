@@ -0,0 +1,136 @@
+use crate::{
+    DynError,
+    errors::{
+        InvalidConfigEntryError,
+        MalformedConfigLineError,
+    },
+    getter_setter::*,
+};
+
+use toml_edit::{
+    DocumentMut,
+    Value,
+};
+
+/// one "Key: value" line of a legacy config file, line is 1
+/// based to match what an editor shows.
+#[derive(Debug, PartialEq)]
+pub struct Entry<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+    pub line: usize,
+}
+
+/// the entries of a config file in the legacy "Key: value"
+/// format, in any order. Blank lines and comments (lines
+/// starting with #) are skipped.
+#[derive(Debug, PartialEq)]
+pub struct Entries<'a>(Vec<Entry<'a>>);
+
+impl<'a> Entries<'a> {
+    pub fn parse(raw: &'a str) -> Result<Self, DynError> {
+        let mut entries: Vec<Entry> = Vec::new();
+
+        for (idx, raw_line) in raw.lines().enumerate() {
+            let line = idx + 1;
+            let trimmed = raw_line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = trimmed
+                .split_once(':')
+                .ok_or(MalformedConfigLineError::new(line))?
+            ;
+
+            let key = key.trim_end();
+
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(MalformedConfigLineError::new(line).into());
+            }
+
+            if let Some(first) = entries.iter().find(|e| e.key == key) {
+                return Err(InvalidConfigEntryError::new(
+                    key,
+                    line,
+                    format!("duplicate of line {}", first.line),
+                ).into());
+            }
+
+            entries.push(Entry { key, value: value.trim(), line });
+        }
+
+        Ok(Self(entries))
+    }
+
+    pub fn find(&self, key: &str) -> Option<&Entry<'a>> {
+        self.0.iter().find(|entry| entry.key == key)
+    }
+}
+
+/// a config which isn't toml but reads as "Key: value" lines
+/// was written before profiles moved to toml.
+pub fn is_legacy(raw: &str) -> bool {
+    raw.parse::<DocumentMut>().is_err() && Entries::parse(raw).is_ok()
+}
+
+/// a legacy config as a toml config in the current layout, it
+/// is built from TEMPLATE and Config::KEY. Keys the program 
+/// doesn't know are kept as top level strings.
+pub fn convert(raw: &str) -> Result<DocumentMut, DynError> {
+    let entries = Entries::parse(raw)?;
+    let mut doc = TEMPLATE.parse::<DocumentMut>()?;
+
+    let known = [
+        migrate_entry::<RelayCount>(&entries, &mut doc)?,
+        migrate_entry::<RelayMax>(&entries, &mut doc)?,
+        migrate_entry::<MaxTime>(&entries, &mut doc)?,
+        migrate_entry::<GpioPin>(&entries, &mut doc)?,
+        migrate_entry::<Timezone>(&entries, &mut doc)?,
+        migrate_entry::<Multiplier>(&entries, &mut doc)?,
+        migrate_entry::<GpioBackend>(&entries, &mut doc)?,
+        migrate_entry::<GpioChip>(&entries, &mut doc)?,
+        migrate_entry::<GpioLine>(&entries, &mut doc)?,
+        migrate_entry::<Polarity>(&entries, &mut doc)?,
+        migrate_entry::<Recovery>(&entries, &mut doc)?,
+        migrate_entry::<Overlap>(&entries, &mut doc)?,
+    ];
+
+    for entry in entries.0.iter().filter(|e| !known.contains(&Some(e.key))) {
+        doc.insert(entry.key, Value::from(entry.value).into());
+    }
+
+    Ok(doc)
+}
+
+/// moves the legacy entry of C into doc unless it's missing or
+/// empty (the key keeps its default), returns its legacy name.
+fn migrate_entry<C: Config>(
+    entries: &Entries,
+    doc: &mut DocumentMut,
+) -> Result<Option<&'static str>, DynError> {
+    let Some(legacy) = C::LEGACY else {
+        return Ok(None);
+    };
+
+    if let Some(entry) = entries
+        .find(legacy)
+        .filter(|entry| !entry.value.is_empty())
+    {
+        let value = C::parser(entry.value)
+            .and_then(C::value)
+            .map_err(|e| {
+                InvalidConfigEntryError::new(
+                    legacy,
+                    entry.line,
+                    e.to_string(),
+                )
+            })?
+        ;
+
+        set_value(doc, C::KEY, value)?;
+    }
+
+    Ok(Some(legacy))
+}
@@ -0,0 +1,132 @@
+use crate::{
+    DynError,
+    file,
+    errors::{
+        InvalidConfigError,
+        UnsupportedVersionError,
+    },
+    getter_setter::legacy,
+};
+
+use std::fs;
+
+use toml_edit::{
+    DocumentMut,
+    Value,
+};
+
+#[cfg(test)]
+mod test;
+
+/// the top level key holding the layout version of a config.
+/// toml configs written before it existed are version 1.
+pub const KEY: &str = "version";
+
+/// upgrades a config by one version, STEPS[n] takes
+/// a version n + 1 config to version n + 2.
+pub type Step = fn(&mut DocumentMut) -> Result<(), DynError>;
+
+/// add a step here whenever a key is moved, renamed or changes
+/// meaning; new keys with a default don't need one.
+pub const STEPS: &[Step] = &[];
+
+/// the version of the configs this program writes.
+pub const VERSION: i64 = STEPS.len() as i64 + 1;
+
+/// the layout version of a toml config.
+pub fn version(doc: &DocumentMut) -> Result<i64, DynError> {
+    match doc.get(KEY) {
+        None => Ok(1),
+        Some(item) => item
+            .as_integer()
+            .filter(|version| *version >= 1)
+            .ok_or(InvalidConfigError::new(format!(
+                "{KEY} has to be a whole number of at least 1, got {item}",
+            )).into()),
+    }
+}
+
+/// brings the config at path to VERSION, a legacy config is
+/// converted to toml first. The caller holds the profiles 
+/// WriteLock. The config is only rewritten when
+/// something changed and the file it replaces is kept next to 
+/// it (config.legacy, config.v1, ..). Returns the upgraded 
+/// config and the version it was at.
+pub fn upgrade(
+    raw: &str,
+    path: &str,
+) -> Result<(String, i64), DynError> {
+    upgrade_with(raw, path, STEPS)
+}
+
+pub fn upgrade_with(
+    raw: &str,
+    path: &str,
+    steps: &[Step],
+) -> Result<(String, i64), DynError> {
+    let latest = steps.len() as i64 + 1;
+
+    let is_legacy = legacy::is_legacy(raw);
+
+    let (mut doc, from) = if is_legacy {
+        // converted straight to the current layout
+        (legacy::convert(raw)?, 0)
+    } else {
+        let doc = raw.parse::<DocumentMut>()?;
+        let from = version(&doc)?;
+        (doc, from)
+    };
+
+    if from > latest {
+        return Err(UnsupportedVersionError::new(from, latest).into());
+    }
+
+    if from == latest {
+        if doc.contains_key(KEY) {
+            return Ok((raw.to_string(), from));
+        }
+
+        // only the version is missing, nothing to back up
+        doc.insert(KEY, Value::from(latest).into());
+        let stamped = doc.to_string();
+        file::write_atomic(path, &stamped)?;
+
+        return Ok((stamped, from));
+    }
+
+    if !is_legacy {
+        for step in &steps[from as usize - 1..] {
+            step(&mut doc)?;
+        }
+    }
+
+    doc.insert(KEY, Value::from(latest).into());
+
+    let backup = backup(raw, path, from)?;
+    let upgraded = doc.to_string();
+    file::write_atomic(path, &upgraded)?;
+
+    println!("{path}: upgraded to version {latest}, the old config is kept as {backup}");
+
+    Ok((upgraded, from))
+}
+
+/// keeps raw next to path, never overwriting an older backup.
+fn backup(raw: &str, path: &str, from: i64) -> Result<String, DynError> {
+    let base = match from {
+        0 => format!("{path}.legacy"),
+        _ => format!("{path}.v{from}"),
+    };
+
+    let mut backup = base.clone();
+    let mut n = 1;
+
+    while fs::exists(&backup)? {
+        backup = format!("{base}.{n}");
+        n += 1;
+    }
+
+    file::write_atomic(&backup, raw)?;
+
+    Ok(backup)
+}
@@ -0,0 +1,85 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    file,
+    getter_setter::{
+        schema::*,
+        TEMPLATE,
+    },
+};
+
+use std::{
+    env,
+    fs,
+};
+
+use toml_edit::Item;
+
+const PROF: &str = "schema_test";
+
+/// a step as a later version might add, moves calibration.multiplier
+/// to calibration.factor.
+fn rename_multiplier(doc: &mut DocumentMut) -> Result<(), DynError> {
+    let multiplier = doc["calibration"]
+        .as_table_mut()
+        .and_then(|table| table.remove("multiplier"))
+        .unwrap_or(Item::None)
+    ;
+
+    doc["calibration"]["factor"] = multiplier;
+    Ok(())
+}
+
+#[test]
+fn template_version() -> Result<(), DynError> {
+    assert_eq!(version(&TEMPLATE.parse::<DocumentMut>()?)?, VERSION);
+    Ok(())
+}
+
+#[test]
+fn upgrade_test() -> Result<(), DynError> {
+    file::init_dirs(PROF)?;
+
+    let dir = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{PROF}")
+    };
+    let path = format!("{dir}/config");
+
+    // a version 1 config from before the version key existed
+    let v1 = "[backend]\npin = 4\n\n[calibration]\nmultiplier = 2.0\n";
+    fs::write(&path, v1)?;
+
+    let steps: &[Step] = &[rename_multiplier];
+
+    let (upgraded, from) = upgrade_with(v1, &path, steps)?;
+    assert_eq!(from, 1);
+    assert_eq!(fs::read_to_string(&path)?, upgraded);
+    assert_eq!(fs::read_to_string(format!("{dir}/config.v1"))?, v1);
+
+    let doc = upgraded.parse::<DocumentMut>()?;
+    assert_eq!(version(&doc)?, 2);
+    assert_eq!(doc["calibration"]["factor"].as_float(), Some(2.0));
+    assert!(doc["calibration"].get("multiplier").is_none());
+
+    assert_eq!(
+        upgrade_with(&upgraded, &path, steps)?,
+        (upgraded.clone(), 2),
+        "an up to date config was upgraded again",
+    );
+    assert!(!fs::exists(format!("{dir}/config.v2"))?);
+
+    assert!(
+        upgrade_with(&upgraded, &path, &[]).is_err(),
+        "a config from a newer version was accepted",
+    );
+    assert_eq!(fs::read_to_string(&path)?, upgraded);
+
+    // only the version key is missing
+    let (stamped, from) = upgrade_with(v1, &path, &[])?;
+    assert_eq!(from, 1);
+    assert_eq!(version(&stamped.parse::<DocumentMut>()?)?, 1);
+    assert!(!fs::exists(format!("{dir}/config.v1.1"))?);
+
+    Ok(fs::remove_dir_all(dir)?)
+}
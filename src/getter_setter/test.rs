@@ -9,9 +9,13 @@ const TEST: &'static str = "test";
 /// returns the profs raw Vec<u8> of data from 
 /// le profile
 fn prof_raw() -> Result<String, DynError> {
+    prof_raw_of(TEST)
+}
+
+fn prof_raw_of(prof: &str) -> Result<String, DynError> {
     let path = {
         let var = env::var("XDG_CONFIG_HOME")?;
-        format!("{var}/{DIR_NAME}/{TEST}/config")
+        format!("{var}/{DIR_NAME}/{prof}/config")
     };
 
     let raw = fs::read_to_string(&path)?;
@@ -19,12 +23,19 @@ fn prof_raw() -> Result<String, DynError> {
     Ok(raw)
 }
 
-fn unlazy_skip(
-    num_nexts: usize, 
-    skipped_upon: &mut impl Iterator,
-) {
-    for _ in 0..num_nexts {
-        let _ = skipped_upon.next();
+/// reads C back from the toml config it was set in
+fn get<C: Config>(raw: &str) -> Result<C, DynError> {
+    let doc = raw.parse::<toml_edit::DocumentMut>()?;
+
+    let item = C::KEY
+        .split('.')
+        .try_fold(doc.as_item(), |item, key| item.get(key))
+        .ok_or("key missing from the config")?
+    ;
+
+    match item.as_str() {
+        Some(raw) => C::parser(raw),
+        None => C::parser(item.to_string().trim()),
     }
 }
 
@@ -38,16 +49,39 @@ fn full_config_new() -> Result<(), DynError> {
     GpioPin::set(String::from("8"), TEST)?;
     Timezone::set(String::from("America/New_York"), TEST)?;
     Multiplier::set(String::from("2.31"), TEST)?;
+    GpioBackend::set(String::from("sim"), TEST)?;
+    GpioChip::set(String::from("gpiochip1"), TEST)?;
+    Polarity::set(String::from("active-low"), TEST)?;
+    Recovery::set(String::from("refuse"), TEST)?;
+    Overlap::set(String::from("skip"), TEST)?;
+    DoseUnit::set(String::from("floz"), TEST)?;
+    FlowRate::set(String::from("128/60"), TEST)?;
+    WarnAt::set(String::from("90%, 50"), TEST)?;
+    Wear::set(String::from("warn"), TEST)?;
 
     assert_eq!(
         FullConfig::new(TEST)?,
         FullConfig { 
+            relay: None,
             relay_count: 120000,
             relay_max: 140000,                           
+            relay_installed: None,
+            relay_history: Vec::new(),
+            warn_at: vec![50.0, 90.0],
+            wear: WearPolicy::Warn,
             max_time: 50.0, 
             gpio_pin: 8,                             
             timezone: Tz::America__New_York,                 
             multiplier: 2.31,                         
+            backend: BackendKind::Sim,
+            gpio_chip: String::from("/dev/gpiochip1"),
+            gpio_line: 8,
+            polarity: ActiveLevel::Low,
+            recovery: Policy::Refuse,
+            overlap: OverlapPolicy::Skip,
+            unit: VolumeUnit::FlOz,
+            flow_rate: Some(128.0 / 60.0),
+            curve: None,
         },                  
         "Failed FullConfig::new test",
     );
@@ -68,9 +102,7 @@ fn relay_count() -> Result<(), DynError> {
     RelayCount::set(test_val.clone(), TEST)?;
 
     let raw = prof_raw()?;
-    let mut lines = raw.lines();
-
-    let tested = RelayCount::get(&mut lines)?;
+    let tested = get::<RelayCount>(&raw)?;
 
     assert_eq!(
             test_val.parse::<u64>()?,
@@ -94,10 +126,7 @@ fn relay_max() -> Result<(), DynError> {
     RelayMax::set(test_val.clone(), TEST)?;
 
     let raw = prof_raw()?;
-    let mut lines = raw.lines();
-    unlazy_skip(1, &mut lines);
-    
-    let tested = RelayMax::get(&mut lines)?;
+    let tested = get::<RelayMax>(&raw)?;
 
     assert_eq!(
         test_val.parse::<u64>()?,
@@ -121,13 +150,10 @@ fn max_time() -> Result<(), DynError> {
     MaxTime::set(test_val.clone(), TEST)?;
 
     let raw = prof_raw()?;
-    let mut lines = raw.lines();
-    unlazy_skip(2, &mut lines);
-
-    let tested = RelayMax::get(&mut lines)?;
+    let tested = get::<MaxTime>(&raw)?;
 
     assert_eq!(
-        test_val.parse::<u64>()?,
+        test_val.parse::<f64>()?,
         tested.0,
         "Failed MaxTime::get/set test",
     );
@@ -148,10 +174,7 @@ fn gpio_pin() -> Result<(), DynError> {
     GpioPin::set(test_val.clone(), TEST)?;
 
     let raw = prof_raw()?;
-    let mut lines = raw.lines();
-    unlazy_skip(3, &mut lines);
-
-    let tested = GpioPin::get(&mut lines)?;
+    let tested = get::<GpioPin>(&raw)?;
 
     assert_eq!(
         test_val.parse::<u8>()?,
@@ -175,10 +198,7 @@ fn timezone_test() -> Result<(), DynError> {
     Timezone::set(test_val.clone(), TEST)?;
 
     let raw = prof_raw()?;
-    let mut lines = raw.lines();
-    unlazy_skip(4, &mut lines);
-
-    let tested = Timezone::get(&mut lines)?;
+    let tested = get::<Timezone>(&raw)?;
 
     assert_eq!(
             Tz::from_str_insensitive(&test_val)?,
@@ -202,16 +222,25 @@ fn multiplier() -> Result<(), DynError> {
     Multiplier::set(test_val.clone(), TEST)?;
 
     let raw = prof_raw()?;
-    let mut lines = raw.lines();
-    unlazy_skip(5, &mut lines);
-
-    let tested = Multiplier::get(&mut lines)?;
+    let tested = get::<Multiplier>(&raw)?;
 
     assert_eq!(
-        test_val.parse::<f64>()?,    
+        test_val,
         tested.0,
         "Failed Multiplier::get/set test",
     );
+
+    let test_val = String::from("(1/0.0338)*1.25");
+    Multiplier::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    assert!(
+        raw.contains("multiplier = \"(1/0.0338)*1.25\""),
+        "the expression was not stored verbatim",
+    );
+    assert_eq!(get::<Multiplier>(&raw)?.0, test_val);
+
+    assert!(Multiplier::set(String::from("1/0"), TEST).is_err());
     
     let path = {                                
         let var = env::var("XDG_CONFIG_HOME")?; 
@@ -220,3 +249,482 @@ fn multiplier() -> Result<(), DynError> {
 
     Ok(fs::remove_dir_all(path)?)
 }
+
+#[test]
+fn gpio_backend() -> Result<(), DynError> {
+    file::init_dirs(TEST)?;
+
+    let test_val = String::from("sim");
+    GpioBackend::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    let tested = get::<GpioBackend>(&raw)?;
+
+    assert_eq!(
+        test_val.parse::<BackendKind>()?,
+        tested.0,
+        "Failed GpioBackend::get/set test",
+    );
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{TEST}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn gpio_chip() -> Result<(), DynError> {
+    file::init_dirs(TEST)?;
+
+    let test_val = String::from("/dev/gpiochip2");
+    GpioChip::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    let tested = get::<GpioChip>(&raw)?;
+
+    assert_eq!(
+        test_val,
+        tested.0,
+        "Failed GpioChip::get/set test",
+    );
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{TEST}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn gpio_line() -> Result<(), DynError> {
+    file::init_dirs(TEST)?;
+
+    let test_val = String::from("21");
+    GpioLine::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    let tested = get::<GpioLine>(&raw)?;
+
+    assert_eq!(
+        test_val.parse::<u32>()?,
+        tested.0,
+        "Failed GpioLine::get/set test",
+    );
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{TEST}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn polarity() -> Result<(), DynError> {
+    file::init_dirs(TEST)?;
+
+    let test_val = String::from("active-low");
+    Polarity::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    let tested = get::<Polarity>(&raw)?;
+
+    assert_eq!(
+        test_val.parse::<ActiveLevel>()?,
+        tested.0,
+        "Failed Polarity::get/set test",
+    );
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{TEST}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn recovery() -> Result<(), DynError> {
+    file::init_dirs(TEST)?;
+
+    let test_val = String::from("resume");
+    Recovery::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    let tested = get::<Recovery>(&raw)?;
+
+    assert_eq!(
+        test_val.parse::<Policy>()?,
+        tested.0,
+        "Failed Recovery::get/set test",
+    );
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{TEST}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn overlap() -> Result<(), DynError> {
+    file::init_dirs(TEST)?;
+
+    let test_val = String::from("fail");
+    Overlap::set(test_val.clone(), TEST)?;
+
+    let raw = prof_raw()?;
+    let tested = get::<Overlap>(&raw)?;
+
+    assert_eq!(
+        test_val.parse::<OverlapPolicy>()?,
+        tested.0,
+        "Failed Overlap::get/set test",
+    );
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{TEST}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn dose_unit() -> Result<(), DynError> {
+    let prof = "test_dose_unit";
+    file::init_dirs(prof)?;
+
+    DoseUnit::set(String::from("fLoz"), prof)?;
+
+    let raw = prof_raw_of(prof)?;
+    assert!(raw.contains("unit = \"floz\""));
+    assert_eq!(get::<DoseUnit>(&raw)?.0, VolumeUnit::FlOz);
+
+    assert!(DoseUnit::set(String::from("cup"), prof).is_err());
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{prof}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn flow_rate() -> Result<(), DynError> {
+    let prof = "test_flow_rate";
+    file::init_dirs(prof)?;
+
+    FlowRate::set(String::from("12.5"), prof)?;
+    assert_eq!(get::<FlowRate>(&prof_raw_of(prof)?)?.0, "12.5");
+
+    FlowRate::set(String::from("1gal/1min"), prof)?;
+    assert!(
+        prof_raw_of(prof)?.contains("flow_rate = \"1gal/1min\""),
+        "the expression was not stored verbatim",
+    );
+
+    assert!(FlowRate::set(String::from("0"), prof).is_err());
+    assert!(FlowRate::set(String::from("-2"), prof).is_err());
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{prof}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn calibration_curve() -> Result<(), DynError> {
+    let prof = "test_calibration_curve";
+    file::init_dirs(prof)?;
+    GpioPin::set(String::from("17"), prof)?;
+
+    CalibrationCurve::set(String::from("10:40, 2:5"), prof)?;
+
+    let raw = prof_raw_of(prof)?;
+    assert!(raw.contains(
+        "curve = [{ seconds = 2.0, amount = 5.0 }, { seconds = 10.0, amount = 40.0 }]"
+    ));
+
+    let points = FullConfig::new(prof)?
+        .curve
+        .map(|curve| curve.points().to_vec())
+    ;
+    assert_eq!(
+        points,
+        Some(vec![
+            Trial { seconds: 2.0, amount: 5.0 },
+            Trial { seconds: 10.0, amount: 40.0 },
+        ]),
+    );
+
+    for invalid in ["", "2", "2:5,10:4", "2:5,2:6", "0:1", "2:-5"] {
+        assert!(
+            CalibrationCurve::set(String::from(invalid), prof).is_err(),
+            "{invalid:?} should not be set",
+        );
+    }
+
+    let path = {                                
+        let var = env::var("XDG_CONFIG_HOME")?; 
+        format!("{var}/{DIR_NAME}/{prof}")      
+    };                                          
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+/// a reordered toml config with comments and a key this 
+/// program doesn't know about.
+#[test]
+fn hand_edited_config() -> Result<(), DynError> {
+    let prof = "test_hand_edited";
+    file::init_dirs(prof)?;
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    let raw = r#"# pump for the east beds
+version = 1
+installed = "2024-04-01"
+
+[backend]
+pin = 8 # BCM
+
+[relay]
+max = 1000
+count = 3
+"#;
+
+    fs::write(format!("{path}/config"), raw)?;
+
+    let config = FullConfig::new(prof)?;
+    assert_eq!((config.gpio_pin, config.relay_count), (8, 3));
+
+    RelayCount::set(String::from("4"), prof)?;
+    Polarity::set(String::from("active-low"), prof)?;
+    Multiplier::set(String::from("2.5"), prof)?;
+
+    let edited = prof_raw_of(prof)?;
+    assert!(edited.starts_with("# pump for the east beds\nversion = 1\ninstalled = \"2024-04-01\"\n"));
+    assert!(edited.contains("pin = 8 # BCM\n"), "the comment was dropped");
+    assert!(edited.contains("\ncount = 4\n"));
+    assert!(edited.contains("\npolarity = \"active-low\"\n"));
+    assert!(edited.contains("[calibration]\nmultiplier = 2.5\n"));
+
+    fs::write(format!("{path}/config"), raw.replace("pin = 8", "pin = \"eight\""))?;
+    let err = FullConfig::new(prof)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default()
+    ;
+    assert!(
+        err.contains("line 6") && err.contains("pin"),
+        "the error does not name the key and line: {err}",
+    );
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+/// a config written before profiles moved to toml
+#[test]
+fn legacy_migration() -> Result<(), DynError> {
+    let prof = "test_legacy_migration";
+    file::init_dirs(prof)?;
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    let raw = r#"RelayCount: 3
+RelayMax: 1000
+MaxTime: 30
+GpioPin: 8
+Timezone: america/new_york
+Multiplier: 1.5
+GpioBackend:
+Installed: 2024-04-01"#;
+
+    fs::write(format!("{path}/config"), raw)?;
+
+    let config = FullConfig::new(prof)?;
+    assert_eq!(
+        (config.relay_count, config.gpio_pin, config.multiplier),
+        (3, 8, 1.5),
+    );
+    assert_eq!(config.timezone, Tz::America__New_York);
+    assert_eq!(config.backend, BackendKind::Rppal);
+
+    assert_eq!(fs::read_to_string(format!("{path}/config.legacy"))?, raw);
+
+    let migrated = prof_raw_of(prof)?;
+    assert!(!legacy::is_legacy(&migrated));
+    assert!(
+        migrated.contains("\nInstalled = \"2024-04-01\"\n"),
+        "unknown key was dropped",
+    );
+    assert_eq!(FullConfig::new(prof)?, config);
+
+    fs::write(format!("{path}/config"), raw.replace("GpioPin: 8", "GpioPin: eight"))?;
+    let err = FullConfig::new(prof)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default()
+    ;
+    assert!(
+        err.contains("GpioPin on line 4"),
+        "the error does not name the key and line: {err}",
+    );
+    assert!(
+        !fs::exists(format!("{path}/config.legacy.1"))?,
+        "a config which failed to migrate was backed up",
+    );
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+/// a single --set-gpio on a fresh profile is enough to actuate
+#[test]
+fn defaults() -> Result<(), DynError> {
+    let prof = "test_defaults";
+    file::init_dirs(prof)?;
+
+    GpioPin::set(String::from("17"), prof)?;
+
+    assert_eq!(
+        FullConfig::new(prof)?,
+        FullConfig {
+            relay: None,
+            relay_count: 0,
+            relay_max: 100000,
+            relay_installed: None,
+            relay_history: Vec::new(),
+            warn_at: vec![80.0, 95.0],
+            wear: WearPolicy::Override,
+            max_time: 30.0,
+            gpio_pin: 17,
+            timezone: Tz::UTC,
+            multiplier: 1.0,
+            backend: BackendKind::Rppal,
+            gpio_chip: String::from("/dev/gpiochip0"),
+            gpio_line: 17,
+            polarity: ActiveLevel::High,
+            recovery: Policy::Log,
+            overlap: OverlapPolicy::Wait,
+            unit: VolumeUnit::Ml,
+            flow_rate: None,
+            curve: None,
+        },
+    );
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+#[test]
+fn init_profile_test() -> Result<(), DynError> {
+    let prof = "test_init_profile";
+    file::init_dirs(prof)?;
+
+    init_profile(prof)?;
+    assert_eq!(prof_raw_of(prof)?, TEMPLATE);
+
+    let err = FullConfig::new(prof)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default()
+    ;
+    assert!(
+        err.contains("missing field `pin`"),
+        "the template should only be missing the pin: {err}",
+    );
+
+    MaxTime::set(String::from("12"), prof)?;
+    assert!(
+        init_profile(prof).is_err(),
+        "an existing config was overwritten",
+    );
+
+    GpioPin::set(String::from("4"), prof)?;
+    let config = FullConfig::new(prof)?;
+    assert_eq!((config.max_time, config.gpio_pin), (12.0, 4));
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+/// executions and setters running at once must not lose counts
+#[test]
+fn concurrent_increment() -> Result<(), DynError> {
+    let prof = "test_concurrent_increment";
+    file::init_dirs(prof)?;
+
+    GpioPin::set(String::from("5"), prof)?;
+
+    let threads = (0..8)
+        .map(|_| std::thread::spawn(move || {
+            for _ in 0..5 {
+                RelayCount::increment(prof).map_err(|e| e.to_string())?;
+                Multiplier::set(String::from("1.5"), prof).map_err(|e| e.to_string())?;
+            }
+
+            Ok::<(), String>(())
+        }))
+        .collect::<Vec<_>>()
+    ;
+
+    for thread in threads {
+        thread.join().map_err(|_| "increment thread panicked")??;
+    }
+
+    assert_eq!(FullConfig::new(prof)?.relay_count, 40);
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
+
+/// a count edited into something else is never reset
+#[test]
+fn invalid_count_increment() -> Result<(), DynError> {
+    let prof = "test_invalid_count_increment";
+    file::init_dirs(prof)?;
+
+    GpioPin::set(String::from("5"), prof)?;
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    let raw = fs::read_to_string(format!("{path}/config"))?
+        .replace("count = 0", "count = 41.5")
+    ;
+    fs::write(format!("{path}/config"), &raw)?;
+
+    let err = RelayCount::increment(prof).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("relay.count = 41.5"), "{err}");
+    assert_eq!(fs::read_to_string(format!("{path}/config"))?, raw);
+
+    Ok(fs::remove_dir_all(path)?)
+}
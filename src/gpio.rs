@@ -1,31 +1,370 @@
 use crate::{
     DynError,
+    errors::{
+        InvalidBackendError,
+        InvalidPolarityError,
+    },
     getter_setter::FullConfig,
 };
 
 use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        Mutex,
+        PoisonError,
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+    },
     thread,
     time,
 };
 
-use rppal::gpio::Gpio;
+use signal_hook::{
+    consts::{
+        SIGHUP,
+        SIGINT,
+        SIGQUIT,
+        SIGTERM,
+    },
+    low_level,
+};
+
+use rppal::gpio::{
+    Gpio,
+    OutputPin,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+pub mod cdev;
+pub mod sim;
+
+use cdev::Cdev;
+use sim::Sim;
+
+#[cfg(test)]
+mod test;
+
+/// something that can drive a single gpio pin high and low.
+/// Every actuation goes through this trait so the hardware
+/// access can be swapped out per profile (GpioBackend config entry).
+pub trait Backend {
+    fn set_high(&mut self) -> Result<(), DynError>;
+    fn set_low(&mut self) -> Result<(), DynError>;
+}
+
+/// the level a pin is driven to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Level {
+    High,
+    Low,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::High => "high",
+            Self::Low => "low",
+        })
+    }
+}
+
+/// the level which energizes the relay of a profile (Polarity
+/// config entry). Active-low relay boards switch on when the
+/// pin is pulled low.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ActiveLevel {
+    #[default]
+    High,
+    Low,
+}
+
+impl ActiveLevel {
+    pub fn on_level(self) -> Level {
+        match self {
+            Self::High => Level::High,
+            Self::Low => Level::Low,
+        }
+    }
+
+    /// the level a pin must be left at for the relay to stay off.
+    pub fn off_level(self) -> Level {
+        match self {
+            Self::High => Level::Low,
+            Self::Low => Level::High,
+        }
+    }
+
+    /// energizes the relay
+    pub fn on(self, backend: &mut dyn Backend) -> Result<(), DynError> {
+        set(backend, self.on_level())
+    }
+
+    /// de-energizes the relay
+    pub fn off(self, backend: &mut dyn Backend) -> Result<(), DynError> {
+        set(backend, self.off_level())
+    }
+}
+
+impl FromStr for ActiveLevel {
+    type Err = InvalidPolarityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active-high" => Ok(Self::High),
+            "active-low" => Ok(Self::Low),
+            _ => Err(InvalidPolarityError::new()),
+        }
+    }
+}
+
+impl fmt::Display for ActiveLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::High => "active-high",
+            Self::Low => "active-low",
+        })
+    }
+}
+
+fn set(backend: &mut dyn Backend, level: Level) -> Result<(), DynError> {
+    match level {
+        Level::High => backend.set_high(),
+        Level::Low => backend.set_low(),
+    }
+}
+
+/// the implementations of Backend a profile can select,
+/// the config file stores the lowercase name.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum BackendKind {
+    #[default]
+    Rppal,
+    Cdev,
+    Sim,
+}
+
+impl FromStr for BackendKind {
+    type Err = InvalidBackendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rppal" => Ok(Self::Rppal),
+            "cdev" => Ok(Self::Cdev),
+            "sim" => Ok(Self::Sim),
+            _ => Err(InvalidBackendError::new()),
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Rppal => "rppal",
+            Self::Cdev => "cdev",
+            Self::Sim => "sim",
+        })
+    }
+}
+
+/// memory mapped Raspberry Pi gpio access through the rppal crate.
+pub struct Rppal(OutputPin);
 
-/// converts num ozs from config to num of seconds to remain on
-pub fn amount_converter(amount: usize, multiplier: f64) -> time::Duration {
-    let bind: f64 = amount as f64 * multiplier;
-    time::Duration::from_secs(bind as u64)
+impl Rppal {
+    /// the pin starts out driven at the initial level, and is
+    /// left there once dropped instead of being reset to an input. 
+    /// A floating pin can energize some relay boards.
+    pub fn new(pin: u8, initial: Level) -> Result<Self, DynError> {
+        let gpio = Gpio::new()?;
+        let pin = gpio.get(pin)?;
+
+        let mut out = match initial {
+            Level::High => pin.into_output_high(),
+            Level::Low => pin.into_output_low(),
+        };
+
+        out.set_reset_on_drop(false);
+        Ok(Self(out))
+    }
+}
+
+impl Backend for Rppal {
+    fn set_high(&mut self) -> Result<(), DynError> {
+        self.0.set_high();
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), DynError> {
+        self.0.set_low();
+        Ok(())
+    }
+}
+
+/// opens the backend selected by the profiles configuration,
+/// the pin starts out at the off level of the profiles polarity.
+pub fn backend(
+    config: &FullConfig,
+    prof: &str,
+) -> Result<Box<dyn Backend>, DynError> {
+    let off = config.polarity.off_level();
+
+    Ok(match config.backend {
+        BackendKind::Rppal => Box::new(Rppal::new(config.gpio_pin, off)?),
+        BackendKind::Cdev => Box::new(
+            Cdev::new(&config.gpio_chip, config.gpio_line, off)?
+        ),
+        BackendKind::Sim => Box::new(
+            Sim::with_trace_file(config.gpio_pin, prof)?
+        ),
+    })
 }
 
+/// how often the stop flag is checked while a pin is on.
+const POLL: time::Duration = time::Duration::from_millis(50);
+
+/// how an actuation ended.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Completed,
+    /// a signal cut the actuation short
+    Interrupted,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Completed => "completed",
+            Self::Interrupted => "interrupted",
+        })
+    }
+}
+
+/// what actually happened on the pin.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Actuation {
+    /// seconds the relay was energized for
+    pub seconds: f64,
+    pub outcome: Outcome,
+}
+
+/// energizes the relay for duration or until stop is set, the 
+/// relay is always de-energized before returning.
 pub fn actuator(
     duration: time::Duration,
-    config: &FullConfig,
-) -> Result<(), DynError> {
-    let gpio = Gpio::new()?;
-    let mut pin = gpio.get(config.gpio_pin)?.into_output();
+    backend: &mut dyn Backend,
+    polarity: ActiveLevel,
+    stop: &AtomicBool,
+) -> Result<Actuation, DynError> {
+    actuator_with(duration, backend, polarity, stop, &mut |_| Ok(()))
+}
 
-    pin.set_high();
+/// same as actuator but calls tick with the seconds actuated so 
+/// far every time the stop flag is checked. The relay is turned
+/// off before a tick error is returned.
+pub fn actuator_with(
+    duration: time::Duration,
+    backend: &mut dyn Backend,
+    polarity: ActiveLevel,
+    stop: &AtomicBool,
+    tick: &mut dyn FnMut(f64) -> Result<(), DynError>,
+) -> Result<Actuation, DynError> {
+    if let Err(e) = polarity.on(backend) {
+        let _ = polarity.off(backend);
+        return Err(e);
+    }
+
+    let start = time::Instant::now();
+
+    let interrupted = loop {
+        if stop.load(Ordering::SeqCst) {
+            break true;
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            break false;
+        }
+
+        if let Err(e) = tick(elapsed.as_secs_f64()) {
+            let _ = polarity.off(backend);
+            return Err(e);
+        }
+
+        thread::sleep((duration - elapsed).min(POLL));
+    };
+
+    let seconds = start.elapsed().as_secs_f64();
+
+    polarity.off(backend)?;
+
+    Ok(Actuation {
+        seconds,
+        outcome: if interrupted {
+            Outcome::Interrupted
+        } else {
+            Outcome::Completed
+        },
+    })
+}
+
+/// the signals systemd and terminals use to end a process.
+const SIGNALS: [i32; 4] = [SIGTERM, SIGINT, SIGHUP, SIGQUIT];
+
+static HOOKED: Mutex<bool> = Mutex::new(false);
+static ARMED: AtomicBool = AtomicBool::new(false);
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// while alive, termination signals set a stop flag instead 
+/// of killing the process so that the relay can be turned off 
+/// and the actuation recorded. Once dropped signals terminate
+/// the process as usual again.
+pub struct Signals(());
+
+impl Signals {
+    pub fn catch() -> Result<Self, DynError> {
+        let mut hooked = HOOKED
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        ;
 
-    thread::sleep(duration);
+        if !*hooked {
+            for signal in SIGNALS {
+                // SAFETY: the action only touches atomics and
+                // emulate_default_handler, both async-signal-safe.
+                unsafe {
+                    low_level::register(signal, move || {
+                        if ARMED.load(Ordering::SeqCst) {
+                            STOP.store(true, Ordering::SeqCst);
+                        } else {
+                            let _ = low_level::emulate_default_handler(signal);
+                        }
+                    })?;
+                }
+            }
+
+            *hooked = true;
+        }
+
+        STOP.store(false, Ordering::SeqCst);
+        ARMED.store(true, Ordering::SeqCst);
+        Ok(Self(()))
+    }
+
+    /// set once a termination signal was received.
+    pub fn stop(&self) -> &'static AtomicBool {
+        &STOP
+    }
+}
 
-    Ok(pin.set_low())
+impl Drop for Signals {
+    fn drop(&mut self) {
+        ARMED.store(false, Ordering::SeqCst);
+    }
 }
@@ -0,0 +1,56 @@
+use crate::{
+    DynError,
+    gpio::{
+        Backend,
+        Level,
+    },
+};
+
+use gpio_cdev::{
+    Chip,
+    LineHandle,
+    LineRequestFlags,
+};
+
+/// the label the kernel shows for lines requested by this program
+/// (gpioinfo, /sys/kernel/debug/gpio).
+const CONSUMER: &str = "atg-actuator";
+
+/// linux gpio character device access (/dev/gpiochipN) through
+/// the gpio-cdev crate. Works on any board with a gpio driver in
+/// the kernel, including the gpio-sim module for testing.
+pub struct Cdev(LineHandle);
+
+impl Cdev {
+    /// chip is the path of the character device and line the offset
+    /// of the line on that chip, see the gpioinfo tool for both.
+    /// The line starts out driven at the initial level.
+    pub fn new(
+        chip: &str,
+        line: u32,
+        initial: Level,
+    ) -> Result<Self, DynError> {
+        let default = match initial {
+            Level::High => 1,
+            Level::Low => 0,
+        };
+
+        let mut chip = Chip::new(chip)?;
+        let handle = chip
+            .get_line(line)?
+            .request(LineRequestFlags::OUTPUT, default, CONSUMER)?
+        ;
+
+        Ok(Self(handle))
+    }
+}
+
+impl Backend for Cdev {
+    fn set_high(&mut self) -> Result<(), DynError> {
+        Ok(self.0.set_value(1)?)
+    }
+
+    fn set_low(&mut self) -> Result<(), DynError> {
+        Ok(self.0.set_value(0)?)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    gpio::{
+        Backend,
+        Level,
+    },
+};
+
+use std::{
+    env,
+    fmt,
+    fs::OpenOptions,
+    io::Write,
+    time::Duration,
+};
+
+/// one recorded level change of a simulated pin.
+/// timestamp is CLOCK_MONOTONIC (time since boot) so
+/// transitions from separate executions can be compared.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Transition {
+    pub timestamp: Duration,
+    pub pin: u8,
+    pub prof: String,
+    pub level: Level,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.6} {} GpioPin:{} {}",
+            self.timestamp.as_secs_f64(),
+            self.prof,
+            self.pin,
+            self.level,
+        )
+    }
+}
+
+/// a backend which touches no hardware; it records every level
+/// change it is asked to make in memory and optionally appends
+/// it to a trace file. Useful on machines without gpio pins and
+/// for testing everything which actuates.
+pub struct Sim {
+    pin: u8,
+    prof: String,
+    trace: Vec<Transition>,
+    trace_file: Option<String>,
+}
+
+impl Sim {
+    /// records transitions in memory only.
+    pub fn new(pin: u8, prof: &str) -> Self {
+        Self {
+            pin,
+            prof: prof.to_string(),
+            trace: Vec::new(),
+            trace_file: None,
+        }
+    }
+
+    /// records transitions in memory and appends them,
+    /// one per line, to the profiles sim_trace file.
+    pub fn with_trace_file(pin: u8, prof: &str) -> Result<Self, DynError> {
+        let path = {
+            let var = env::var("XDG_CONFIG_HOME")?;
+            format!("{var}/{DIR_NAME}/{prof}/sim_trace")
+        };
+
+        Ok(Self {
+            trace_file: Some(path),
+            ..Self::new(pin, prof)
+        })
+    }
+
+    pub fn trace(&self) -> &[Transition] {
+        &self.trace
+    }
+
+    fn record(&mut self, level: Level) -> Result<(), DynError> {
+        let transition = Transition {
+            timestamp: monotonic(),
+            pin: self.pin,
+            prof: self.prof.clone(),
+            level,
+        };
+
+        if let Some(path) = &self.trace_file {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+            ;
+
+            writeln!(file, "{transition}")?;
+        }
+
+        self.trace.push(transition);
+        Ok(())
+    }
+}
+
+impl Backend for Sim {
+    fn set_high(&mut self) -> Result<(), DynError> {
+        self.record(Level::High)
+    }
+
+    fn set_low(&mut self) -> Result<(), DynError> {
+        self.record(Level::Low)
+    }
+}
+
+/// std's Instant can't be turned into an absolute value,
+/// read CLOCK_MONOTONIC directly instead.
+fn monotonic() -> Duration {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+
+    // SAFETY: ts is a valid timespec for clock_gettime to write to
+    // and CLOCK_MONOTONIC is always available on linux.
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+    }
+
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
@@ -0,0 +1,536 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    fixture::{
+        self,
+        cleanup,
+    },
+    journal::Journal,
+    getter_setter::*,
+    lock::Locks,
+    record::{
+        self,
+        Event,
+    },
+    gpio::{
+        *,
+        sim::Sim,
+    },
+    unit::Dose,
+};
+
+use std::{
+    env,
+    fs,
+    sync::{
+        Arc,
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+    },
+    thread,
+};
+
+const PROF: &str = "gpio_test";
+
+fn setup(prof: &str) -> Result<(), DynError> {
+    fixture::setup(prof)?;
+
+    RelayCount::set(String::from("7"), prof)?;
+    RelayMax::set(String::from("100"), prof)?;
+    MaxTime::set(String::from("5"), prof)?;
+    Timezone::set(String::from("UTC"), prof)?;
+    Multiplier::set(String::from("2.0"), prof)?;
+
+    Ok(())
+}
+
+#[test]
+fn actuator_test() -> Result<(), DynError> {
+    let mut sim = Sim::new(4, PROF);
+
+    actuator(
+        time::Duration::from_millis(20),
+        &mut sim,
+        ActiveLevel::High,
+        &AtomicBool::new(false),
+    )?;
+
+    let trace = sim.trace();
+    assert_eq!(trace.len(), 2);
+    assert_eq!(
+        (trace[0].level, trace[1].level),
+        (Level::High, Level::Low),
+    );
+    assert!(trace.iter().all(|t| t.pin == 4 && t.prof == PROF));
+    assert!(
+        trace[1].timestamp - trace[0].timestamp
+            >= time::Duration::from_millis(20),
+        "pin was not held high for the duration",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn active_low_test() -> Result<(), DynError> {
+    let mut sim = Sim::new(4, PROF);
+
+    actuator(
+        time::Duration::from_millis(1),
+        &mut sim,
+        ActiveLevel::Low,
+        &AtomicBool::new(false),
+    )?;
+
+    assert_eq!(
+        sim.trace().iter().map(|t| t.level).collect::<Vec<Level>>(),
+        vec![Level::Low, Level::High],
+        "active-low relays are energized by driving the pin low",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn interrupted_test() -> Result<(), DynError> {
+    let mut sim = Sim::new(4, PROF);
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let stopper = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(30));
+            stop.store(true, Ordering::SeqCst);
+        })
+    };
+
+    let actuation = actuator(
+        time::Duration::from_secs(10),
+        &mut sim,
+        ActiveLevel::High,
+        &stop,
+    )?;
+
+    let _ = stopper.join();
+
+    assert_eq!(actuation.outcome, Outcome::Interrupted);
+    assert!(
+        actuation.seconds < 1.0, 
+        "the stop flag did not cut the actuation short",
+    );
+    assert_eq!(
+        sim.trace().last().map(|t| t.level),
+        Some(Level::Low),
+        "the relay was left energized",
+    );
+
+    Ok(())
+}
+
+/// runs FullConfig::execute end to end on a simulated pin
+#[test]
+fn execute_test() -> Result<(), DynError> {
+    let prof = "gpio_test_execute";
+    setup(prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    FullConfig::execute_with(config, Dose::Time(0.01), prof, &mut sim)?;
+
+    let trace = sim.trace();
+    assert_eq!(
+        trace.iter().map(|t| t.level).collect::<Vec<Level>>(),
+        vec![Level::High, Level::Low],
+    );
+    assert!(trace.iter().all(|t| t.pin == 17));
+    assert!(
+        trace[1].timestamp - trace[0].timestamp
+            >= time::Duration::from_millis(20),
+        "multiplier was not applied to the time actuated",
+    );
+
+    assert_eq!(FullConfig::new(prof)?.relay_count, 8);
+
+    let log_path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}/log")
+    };
+    assert!(fs::exists(log_path)?, "execute did not write the log");
+
+    cleanup(prof)
+}
+
+/// an amount is turned into seconds by the flow rate, the 
+/// multiplier is left out
+#[test]
+fn execute_amount_test() -> Result<(), DynError> {
+    let prof = "gpio_test_execute_amount";
+    setup(prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    assert!(
+        FullConfig::execute_with(config, "0.05ml".parse::<Dose>()?, prof, &mut sim)
+            .is_err(),
+        "dosed by amount without a flow rate",
+    );
+
+    FlowRate::set(String::from("2.5"), prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    FullConfig::execute_with(config, "0.05ml".parse::<Dose>()?, prof, &mut sim)?;
+
+    let trace = sim.trace();
+    let on = trace[1].timestamp - trace[0].timestamp;
+    assert!(on >= time::Duration::from_millis(20));
+    assert!(on < time::Duration::from_millis(40), "multiplier was applied");
+
+    let log = record::read(prof)?;
+    assert!(matches!(
+        log.records[0].event,
+        Event::Entry { requested_amount: Some(amount), .. } if amount.to_string() == "0.05ml"
+    ), "{log:?}");
+
+    cleanup(prof)
+}
+
+/// the time actuated for is what gets capped, not the multiplier
+#[test]
+fn max_time_test() -> Result<(), DynError> {
+    let prof = "gpio_test_max_time";
+    setup(prof)?;
+    MaxTime::set(String::from("0.02"), prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    // 2 seconds with the multiplier of 2
+    FullConfig::execute_with(config, Dose::Time(1.0), prof, &mut sim)?;
+
+    let trace = sim.trace();
+    assert!(
+        trace[1].timestamp - trace[0].timestamp < time::Duration::from_millis(500),
+        "the relay was on for longer than the max time",
+    );
+
+    let log = record::read(prof)?;
+    assert!(matches!(
+        log.records[0].event,
+        Event::Entry { requested_seconds: 2.0, clamped: true, .. }
+    ), "{log:?}");
+
+    for invalid in [f64::NAN, f64::INFINITY, -1.0] {
+        let config = FullConfig::new(prof)?;
+        let mut sim = Sim::new(config.gpio_pin, prof);
+
+        assert!(FullConfig::execute_with(config, Dose::Time(invalid), prof, &mut sim).is_err());
+        assert!(sim.trace().is_empty(), "{invalid} seconds were actuated");
+    }
+
+    assert_eq!(FullConfig::new(prof)?.relay_count, 8);
+
+    assert!(MaxTime::set(String::from("NaN"), prof).is_err());
+    assert!(MaxTime::set(String::from("-5"), prof).is_err());
+
+    cleanup(prof)
+}
+
+#[test]
+fn simulate_test() -> Result<(), DynError> {
+    let prof = "gpio_test_simulate";
+    setup(prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let trace = FullConfig::simulate(config, Dose::Time(0.01), prof)?;
+
+    assert_eq!(
+        trace.iter().map(|t| t.level).collect::<Vec<Level>>(),
+        vec![Level::High, Level::Low],
+    );
+
+    let trace_file = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        fs::read_to_string(format!("{var}/{DIR_NAME}/{prof}/sim_trace"))?
+    };
+    assert_eq!(
+        trace_file,
+        trace
+            .iter()
+            .map(|t| format!("{t}\n"))
+            .collect::<String>(),
+    );
+
+    assert_eq!(
+        FullConfig::new(prof)?.relay_count,
+        7,
+        "simulate should not count an actuation",
+    );
+
+    cleanup(prof)
+}
+
+/// needs a simulated chip from the gpio-sim kernel module, ex.
+/// GPIO_SIM_CHIP=/dev/gpiochip1 
+/// GPIO_SIM_VALUE=/sys/devices/platform/gpio-sim.0/gpiochip1/sim_gpio0/value
+/// cargo test -- --ignored cdev_test
+#[test]
+#[ignore]
+fn cdev_test() -> Result<(), DynError> {
+    let chip = env::var("GPIO_SIM_CHIP")?;
+    let value = env::var("GPIO_SIM_VALUE")?;
+
+    let mut cdev = cdev::Cdev::new(&chip, 0, Level::Low)?;
+
+    cdev.set_high()?;
+    assert_eq!(fs::read_to_string(&value)?.trim(), "1");
+
+    cdev.set_low()?;
+    assert_eq!(fs::read_to_string(&value)?.trim(), "0");
+
+    Ok(())
+}
+
+#[test]
+fn safe_state_test() -> Result<(), DynError> {
+    let prof = "gpio_test_safe_state";
+    setup(prof)?;
+    Polarity::set(String::from("active-low"), prof)?;
+    // executions of the other tests hold pin 17
+    GpioPin::set(String::from("201"), prof)?;
+
+    let dir = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}")
+    };
+
+    // an actuation which never finished
+    Journal::new(3.0, 17).write(prof)?;
+
+    // or one which is still running
+    let held = Locks::acquire(&FullConfig::new(prof)?, prof)?;
+    assert_eq!(FullConfig::new(prof)?.safe_state(prof)?, None);
+    assert!(!FullConfig::new(prof)?.force_off(prof)?);
+    assert_eq!(
+        FullConfig::new(prof)?.relay_count,
+        7,
+        "a running actuation was counted as interrupted",
+    );
+    drop(held);
+
+    assert_eq!(FullConfig::new(prof)?.safe_state(prof)?, Some(true));
+    assert_eq!(
+        Journal::read(prof)?,
+        None,
+        "the log recovery policy needs nothing more from the journal",
+    );
+    assert_eq!(
+        FullConfig::new(prof)?.relay_count,
+        8,
+        "the interrupted actuation was not counted",
+    );
+
+    assert_eq!(FullConfig::new(prof)?.safe_state(prof)?, Some(false));
+
+    assert_eq!(
+        FullConfig::new(prof)?.relay_count,
+        8,
+        "a completed actuation was counted again",
+    );
+
+    let trace = fs::read_to_string(format!("{dir}/sim_trace"))?;
+    assert!(
+        trace.lines().all(|line| line.ends_with("high")),
+        "an active-low pin must be driven high to be off",
+    );
+
+    let previous = record::read(prof)?
+        .records
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::SafeState { previous } => Some(previous),
+            _ => None,
+        })
+        .collect::<Vec<Outcome>>()
+    ;
+    assert_eq!(previous, vec![Outcome::Interrupted, Outcome::Completed]);
+
+    cleanup(prof)
+}
+
+#[test]
+fn resume_test() -> Result<(), DynError> {
+    let prof = "gpio_test_resume";
+    setup(prof)?;
+    Recovery::set(String::from("resume"), prof)?;
+
+    Journal {
+        elapsed: 0.01,
+        ..Journal::new(0.03, 17)
+    }.write(prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    FullConfig::execute_with(config, Dose::Time(0.01), prof, &mut sim)?;
+
+    let trace = sim.trace();
+    assert_eq!(
+        trace.len(),
+        4,
+        "the rest of the unfinished actuation was not resumed first",
+    );
+    assert!(
+        trace[1].timestamp - trace[0].timestamp
+            >= time::Duration::from_millis(20),
+        "resumed for less than the remaining seconds",
+    );
+
+    // interrupted, resumed and requested actuation
+    assert_eq!(FullConfig::new(prof)?.relay_count, 10);
+    assert_eq!(Journal::read(prof)?, None);
+
+    cleanup(prof)
+}
+
+/// an actuation counted but cut short before its journal was
+/// cleared (ex. a power cut) is not counted again
+#[test]
+fn counted_journal_test() -> Result<(), DynError> {
+    let prof = "gpio_test_counted_journal";
+    setup(prof)?;
+
+    let log = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{prof}/log")
+    };
+
+    // the log can't be written, the actuation fails after it was
+    // counted and leaves its journal behind
+    fs::create_dir(&log)?;
+    assert!(fixture::execute(prof).is_err());
+    fs::remove_dir(&log)?;
+
+    assert_eq!(FullConfig::new(prof)?.relay_count, 8);
+    assert!(Journal::read(prof)?.is_some_and(|journal| journal.recorded));
+
+    fixture::execute(prof)?;
+
+    assert_eq!(
+        FullConfig::new(prof)?.relay_count,
+        9,
+        "the counted actuation was counted again as interrupted",
+    );
+    assert_eq!(Journal::read(prof)?, None);
+    assert!(
+        record::read(prof)?
+            .records
+            .iter()
+            .all(|record| !matches!(record.event, Event::Interrupted { .. })),
+        "the counted actuation was logged again as interrupted",
+    );
+
+    cleanup(prof)
+}
+
+#[test]
+fn refuse_test() -> Result<(), DynError> {
+    let prof = "gpio_test_refuse";
+    setup(prof)?;
+    Recovery::set(String::from("refuse"), prof)?;
+
+    Journal::new(3.0, 17).write(prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+
+    assert!(FullConfig::execute_with(config, Dose::Time(0.01), prof, &mut sim).is_err());
+    assert!(sim.trace().is_empty(), "actuated without an acknowledgement");
+
+    let config = FullConfig::new(prof)?;
+    assert_eq!(
+        config.relay_count,
+        8,
+        "the interruption was not counted",
+    );
+    assert!(
+        FullConfig::execute_with(config, Dose::Time(0.01), prof, &mut sim).is_err(),
+        "the interruption was only refused once",
+    );
+
+    assert!(FullConfig::new(prof)?.acknowledge(prof)?);
+    assert!(!FullConfig::new(prof)?.acknowledge(prof)?);
+
+    let config = FullConfig::new(prof)?;
+    FullConfig::execute_with(config, Dose::Time(0.01), prof, &mut sim)?;
+
+    assert_eq!(sim.trace().len(), 2);
+    assert_eq!(
+        FullConfig::new(prof)?.relay_count,
+        9,
+        "the interruption was counted more than once",
+    );
+
+    cleanup(prof)
+}
+
+#[test]
+fn overlap_skip_test() -> Result<(), DynError> {
+    let prof = "gpio_test_overlap_skip";
+    setup(prof)?;
+    Overlap::set(String::from("skip"), prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let held = Locks::acquire(&config, prof)?;
+
+    let mut sim = Sim::new(config.gpio_pin, prof);
+    FullConfig::execute_with(config, Dose::Time(0.01), prof, &mut sim)?;
+
+    assert!(sim.trace().is_empty(), "actuated while another execution held the lock");
+    assert_eq!(FullConfig::new(prof)?.relay_count, 7);
+
+    assert!(
+        record::read(prof)?
+            .records
+            .iter()
+            .any(|record| matches!(record.event, Event::Skipped { .. })),
+        "the skipped execution was not logged",
+    );
+
+    drop(held);
+    cleanup(prof)
+}
+
+#[test]
+fn force_off_test() -> Result<(), DynError> {
+    let (prof, other) = ("gpio_test_force_off", "gpio_test_force_off_other");
+
+    // two profiles sharing a pin no other test uses
+    for prof in [prof, other] {
+        setup(prof)?;
+        GpioPin::set(String::from("202"), prof)?;
+    }
+
+    let held = Locks::acquire(&FullConfig::new(other)?, other)?;
+    assert!(
+        !FullConfig::new(prof)?.force_off(prof)?,
+        "drove off a pin another profile was actuating",
+    );
+
+    drop(held);
+    assert!(FullConfig::new(prof)?.force_off(prof)?);
+
+    let trace = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        fs::read_to_string(format!("{var}/{DIR_NAME}/{prof}/sim_trace"))?
+    };
+    assert_eq!(trace.lines().count(), 1);
+
+    cleanup(other)?;
+    cleanup(prof)
+}
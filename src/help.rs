@@ -7,30 +7,123 @@ This is the cli interface for this application.
 Displays this message.
 
 
+Every profile keeps a log ($XDG_CONFIG_HOME/.Actuators/<profile>/log) of what it did, one JSON object per line which is only ever appended to. Each record has the profile, the gpio pin, an RFC 3339 timestamp and its kind: entry (an actuation, with the seconds requested and actually actuated, whether they were capped to the max time and how it ended), interrupted, safe-state, skipped, wear, worn-out or reconciled. Logs from before records were JSON lines (a log which starts with [ENTRY] and has no JSON records) are converted the first time they are read or written to, the old log is kept next to it as log.legacy, or log.legacy.1 and so on if that exists already. Any other log is read as JSON lines. Records which can't be converted are left out of the log but not forgotten, see --reconcile.
+
+
 --show:
-Shows the configuration for every profile and any active timers.
+Shows the configuration for every profile and any active timers, along with how much of the relays rated life (relay.max) is left and when it is expected to run out (see --forecast), followed by every relay added with --add-relay.
+
+
+--forecast:
+Estimates the day the relay of every profile, and every relay added with --add-relay, will reach its max actuations. Actuations per day are worked out both from the profiles log (the actuations of the last 30 days, or of the days since the first one logged) and from its timer entries (see --entry), and the higher of the two is used so a replacement can be ordered before the relay fails. A relay shared by several profiles adds up the actuations of all of them.
+
+
+--maintenance-log:
+Shows the relays every profile and every relay added with --add-relay went through: when each retired relay was installed and retired, the actuations it was counted for and the days it lasted, followed by the relay in service and the average a retired relay lasted. Profiles linked to a relay (--set-relay) are shown as the relay. Relays counted before install dates were kept show an unknown install date.
 
 
 --exec:
 Executes the program based on the configuration of the given profile. This is not a reccomended public interface. This made for systemd timers to "actuate" via activating their corresponding profiles systemd service.
 
-arg order = (1:profile-id) (2:f64-format-time-actuated-for)
+arg order = (optional:--simulate) (optional:--override-wear) (1:profile-id) (2:f64-format-time-actuated-for-or-amount-with-unit)
+
+A plain number is a time in seconds which is multiplied by the profiles multiplier. A number followed by a unit (ml, l, floz or gal, ex. 250mL or 8floz) is an amount, it is turned into seconds by the profiles calibration curve (see --set-curve) or else divided by its flow rate (see --set-flow-rate) instead and the log records the amount requested next to the seconds actuated.
+
+A time which isn't a number, is infinite or negative is refused and nothing is actuated.
+
+With --simulate no gpio pin is touched regardless of the profiles backend. The pin changes are printed once the run is over and appended with a monotonic timestamp to the profiles sim_trace file; the relay count and log are left as they are. This lets a schedule be rehearsed on a desktop.
+
+With --override-wear a worn out relay (see --set-wear) actuates anyway when the profiles wear policy is override.
+
+If the process is told to stop (SIGTERM, SIGINT, SIGHUP, SIGQUIT) while the relay is on, the relay is turned off, the actuation is counted and logged as interrupted with the seconds it actually ran, and the program exits with an error.
+
+
+--dispense:
+Same as --exec but always doses by amount; an amount without a unit (ex. 250) is in the profiles unit (see --set-unit). The profile needs a calibration curve or a flow rate, and the seconds it works out to are still limited to the profiles max time.
+
+arg order = (optional:--simulate) (optional:--override-wear) (1:profile-id) (2:amount-with-optional-unit)
+
+
+--force-off:
+Drives the gpio pin of the given profile to its off level. The systemd services created by --entry run this whenever an actuation stops, including when systemd kills one that ran past the profiles max time. A pin held by an execution of another profile sharing it is left alone.
+
+arg order = (1:profile-id)
+
+
+--safe-state:
+Drives the gpio pin of every profile to its off level and records in each profiles log whether the previous actuation was interrupted (ex. by a power cut) before it could finish. Interrupted actuations are added to the number of actuations, and are left for the next --exec to recover from unless the profiles recovery policy is log. Profiles with an execution running on them or their pin are skipped.
+
+
+--ack:
+Acknowledges an actuation of the given profile which never finished (ex. power cut), allowing the profile to actuate again when its recovery policy is refuse.
+
+arg order = (1:profile-id)
+
+
+--reconcile:
+Compares the relay count of the given profile with the actuations its log accounts for: every entry and interrupted record (finished and interrupted actuations) logged since the relay was installed (see --replace-relay), an install date without a time counts from the start of that day in UTC. A profile linked to a relay (--set-relay) is compared with the logs of every profile linked to it. Log lines which can't be read are reported with their line numbers, as are the lines of its log.legacy backups which couldn't be converted; entries among those still count as actuations. Given count, the count is set to what the log accounts for; given log, a reconciled record accounting for the difference is added to the log.
+
+arg order = (1:profile-id) (optional:count-or-log)
+
+
+--init-profile:
+Creates the given profile with a complete toml config where every key is described by a comment and set to its default. Only backend.pin has no default and has to be set with --set-gpio, or the profile linked to a relay with --set-relay, before the profile can actuate. An existing config is never overwritten.
+
+Any --set option used on a profile which doesn't exist yet also writes this config before setting its value, so keys which are never set keep their defaults: relay.count 0, relay.max 100000, relay.warn_at 80 and 95, relay.worn override, relay.polarity active-high, backend.kind rppal, backend.chip /dev/gpiochip0, backend.line the same as backend.pin, safety.max_time 30, safety.recovery log, safety.overlap wait, calibration.multiplier 1.0, dosing.unit ml and timezone UTC. dosing.flow_rate and calibration.curve have no default, they are only needed to dose by amount. relay.name has no default either, without it the profile counts its own actuations.
+
+Configs from before profiles used toml ("Key: value" lines) are converted the first time they are read; the old file is kept next to it as config.legacy.
+
+arg order = (1:profile-id)
+
+
+--migrate:
+Upgrades the config of every profile to the version this program writes (the version key). Configs are also upgraded one at a time whenever they are read, this does all of them at once, ex. after updating the program. Each upgraded config is kept next to the new one as config.vN (config.legacy for "Key: value" configs). A config written by a newer version of the program is left as it is and reported as an error.
 
 
 --set-num-actuations:
-Sets the number of actuations for the given profile. This exists because relays have a lifetime measured in number of actuations.
+Sets the number of actuations for the given profile. This exists because relays have a lifetime measured in number of actuations. A profile linked to a relay (--set-relay) sets the count of the relay, which every profile linked to it shares.
 
 arg order = (1:profile-id) (2:u64-format-num-actuations-set)
 
 
 --set-max-actuations:
-Sets the number of max actuations for the given profile; This is based on the relay in use. This may be unimportant in certain applications.
+Sets the number of max actuations for the given profile; This is based on the relay in use. This may be unimportant in certain applications. A profile linked to a relay (--set-relay) sets the max of the relay.
 
 arg order = (1:profile-id) (2:u64-format-max-num-actuations)
 
 
+--set-wear-warnings:
+Sets the percentages of the max actuations at which the given profile prints a warning and logs a wear record, once each time the count passes one. Defaults to 80,95; an empty string ("") turns the warnings off.
+
+arg order = (1:profile-id) (2:comma-separated-percentages)
+
+
+--set-wear:
+Sets what an execution of the given profile does once the number of actuations reaches the max actuations. "refuse" never actuates until the relay is replaced (--replace-relay), "override" (the default) refuses unless --exec or --dispense is given --override-wear, and "warn" actuates with a warning. Every execution of a worn out relay is logged as a worn-out record with what was done.
+
+arg order = (1:profile-id) (2:refuse-override-or-warn)
+
+
+--add-relay:
+Adds a physical relay to the relay registry ($XDG_CONFIG_HOME/.Actuators/.relays.toml) with the gpio pin driving it and the number of actuations it is rated for. Its count starts at 0 and the date it was added is kept as its install date. Names are made of letters, digits, - and _.
+
+arg order = (1:relay-name) (2:u8-format-gpio-pin) (3:u64-format-max-num-actuations)
+
+
+--set-relay:
+Links the given profile to a relay added with --add-relay. The profile then drives the relays pin, and its actuations are counted against the relays count and max instead of the profiles own relay.count and relay.max, so several profiles switching the same relay wear it as one. A backend.pin set on the profile has to match the relays pin.
+
+arg order = (1:profile-id) (2:relay-name)
+
+
+--replace-relay:
+Retires the relay of the given profile after it was replaced: its count, max actuations, install date and the date it was retired are kept in the relays history (see --maintenance-log), and the new relay starts at 0 actuations, installed now. A profile linked to a relay (--set-relay) replaces the registered relay, which every profile linked to it shares. The new relay is rated for as many actuations as the old one unless a max is given. Use this instead of --set-num-actuations 0, which keeps no history.
+
+arg order = (1:profile-id) (optional:u64-format-max-num-actuations)
+
+
 --set-max-time:
-Sets the maximum amount of time the gpio pin can be actuated for for the given profile. Any value inputed above the max will default to the max value.
+Sets the maximum amount of time the gpio pin can be actuated for for the given profile, a finite number of seconds of at least 0. Every actuation is capped to it after the multiplier, calibration curve or flow rate is applied; a capped actuation prints a warning and is logged with the seconds requested next to the seconds actuated.
 
 arg order =  (1:profile-id) (2:f64-format-max-time-actuated)
 
@@ -41,6 +134,12 @@ Sets the gpio pin actuated for the given profile.
 arg order = (1:prof-id) (2:u8-format-gpio-pin)
 
 
+--set-polarity:
+Sets whether the relay of the given profile is energized by driving the gpio pin high (active-high, the default) or low (active-low). Many relay boards are active-low. The pin is always initialized to, and left at, the off level for the polarity.
+
+arg order = (1:prof-id) (2:active-high-or-active-low)
+
+
 --set-timezone:
 Takes an argument to set the timezone for the given profile; You can use any timezone in the IANA database provided by the chrono_tz crate. (accessible via docs.rs, chrono_tz crate, Tz enum)
 
@@ -48,18 +147,84 @@ arg order = (1:prof-id) (2:chrono_tz::Tz-format-timezone-string)
 
 
 --set-multiplier:
-Sets the multiplier for the given profile. (f64-formatting ex. 0.1, 1.1, 1.0, do not drop the leading or trailing and always include a zero)
+Sets the multiplier for the given profile, the time actuated for is multiplied by it. Either a number (ex. 0.1, 1.1, 1.0) or an arithmetic expression with + - * / and parentheses, ex. "(1/0.0338)*1.25"; quote expressions so the shell leaves them alone. The expression is stored as it was written and computed exactly (as a fraction) before being used.
+
+Named constants can be used in expressions, volumes in millilitres: ml, l, floz, gal; and times in seconds: s, min, h. A number directly followed by a constant is multiplied by it, ex. "60/1gal" for a pump moving a gallon a minute.
+
+arg order = (1:prof-id) (2:multiplier-number-or-expression)
+
+
+--calibrate:
+Works out the multiplier of the given profile by actuating it. It asks how many seconds each trial lasts and how many trials to run, actuates the relay for every trial and asks how much it put out, in the profiles unit (see --set-unit). The multiplier is then the total seconds over the total output, so afterwards --exec takes amounts of that unit, and the flow rate (see --set-flow-rate) the total output over the total seconds, so --dispense and amounts given to --exec are dosed at the calibrated rate. A calibration curve (see --set-curve) is left as it is and still takes precedence over the flow rate. Once confirmed it is stored along with the date and the trials, which are appended to calibration.history in the profile so earlier calibrations are kept.
+
+The trials are counted and logged like any other actuation and are limited to the profiles max time.
+
+arg order = (1:prof-id)
+
+
+--set-curve:
+Sets the calibration curve of the given profile: how much it puts out (in the profiles unit) after so many seconds, as seconds:amount points, ex. 2:5,10:40 for 5 after 2 seconds and 40 after 10. Pumps and valves take a moment to get going, so their output isn't proportional to the time they are on and a flow rate over- or under-doses short runs. With a curve, amounts are turned into seconds by interpolating between the two points around them (nothing is put out at 0 seconds, past the last point the last two points carry on) and still limited to the profiles max time. More seconds have to put out more. The curve is stored as calibration.curve, remove it from the config to go back to the flow rate.
+
+arg order = (1:prof-id) (2:seconds:amount-points-comma-separated)
+
+
+--set-backend:
+Sets how the gpio pin of the given profile is driven. "rppal" (the default) uses the Raspberry Pi gpio registers through the rppal crate. "sim" does not touch any hardware, it prints the pin changes and records them in the profiles sim_trace file, which lets the --exec path run on machines without gpio pins. "cdev" uses the linux gpio character device (/dev/gpiochipN) and works on any board with a kernel gpio driver; it drives the line set with --set-chip and --set-line instead of the gpio pin.
+
+arg order = (1:prof-id) (2:gpio-backend-name)
+
 
-arg order = (1:prof-id) (2:f64-format-time-actuated-multiplier)
+--set-recovery:
+Sets what the next --exec of the given profile does when a previous actuation never finished (ex. power cut). The unfinished actuation is always counted and logged as interrupted first. "log" (the default) then carries on as usual, "resume" first actuates for the rest of the unfinished actuation and "refuse" refuses to actuate until --ack is used on the profile.
+
+arg order = (1:prof-id) (2:log-resume-or-refuse)
+
+
+--set-overlap:
+Sets what an --exec of the given profile does while another execution is actuating the same profile or gpio pin; executions never overlap. "wait" (the default) waits for the other execution to finish, "skip" does not actuate and only logs that the execution was skipped, and "fail" does not actuate and exits with an error.
+
+arg order = (1:prof-id) (2:wait-skip-or-fail)
+
+
+--set-chip:
+Sets the gpio character device used by the cdev backend for the given profile. Either the device path or the chip name may be given (ex. /dev/gpiochip0 or gpiochip0). Defaults to /dev/gpiochip0.
+
+arg order = (1:prof-id) (2:gpio-character-device-path-or-name)
+
+
+--set-line:
+Sets the line offset on the chip used by the cdev backend for the given profile; see the gpioinfo tool for the available lines. Defaults to the gpio pin number.
+
+arg order = (1:prof-id) (2:u32-format-gpio-line-offset)
+
+
+--set-unit:
+Sets the unit the given profile doses in: ml (the default), l, floz or gal. The flow rate is in this unit, and so are amounts given to --dispense without one.
+
+arg order = (1:prof-id) (2:ml-l-floz-or-gal)
+
+
+--set-flow-rate:
+Sets how much of the profiles unit the relay lets through per second, which turns amounts into seconds. Either a number above 0 or an expression like the ones --set-multiplier takes, ex. "1000/45" for a litre in 45 seconds; note the volume constants in expressions are in millilitres.
+
+arg order = (1:prof-id) (2:units-per-second-number-or-expression)
 
 
 --entry:
 Makes an actuation occur at the given time based on the configuration of the given profile. Under the hood this creates a unique systemd timer which points to a profile wide systemd service. This program is systemd based on therefore will not work on non-systemd based systems.
 
-arg order = (1:prof-id) (2:f64-format-time-actuated-for) (3:systemd-timer-file-name-prefix) (4:time-actuated-at)
+The profile must be configured before creating an entry, nothing is written for a profile or dose which can't actuate. Every entry of a profile runs the same service, so an entry with another dose than the profiles existing entries is refused. The service forces the pin off after it stops and is limited by systemd to the longest a healthy run can take (plus a few seconds of slack): the max time, twice that under the resume recovery policy, and under the wait overlap policy also a run of every other profile on the same pin. Re-run --entry after changing any of those to update the service.
+
+The time actuated for can also be an amount with a unit, like with --exec, as long as the profile has a flow rate.
+
+arg order = (1:prof-id) (2:f64-format-time-actuated-for-or-amount-with-unit) (3:systemd-timer-file-name-prefix) (4:time-actuated-at)
 
 
 --del-entry:
 Deletes the entry for the timer file name prefix given. To gather a list of available entries for deletion use the --show argument. This function will automatically cleanup unused service files, you do not have to worry about manual systemd file management related to this program.
 
-arg order = (1:systemd-file-name-prefix)"#;
+arg order = (1:systemd-file-name-prefix)
+
+
+--install-safe-state:
+Installs and enables a oneshot systemd service which runs --safe-state early during boot, so relays never stay in an undefined state after a power cut. XDG_CONFIG_HOME is read from /etc/environment."#;
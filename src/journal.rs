@@ -0,0 +1,167 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    file,
+    errors::{
+        InvalidPolicyError,
+        MalformedJournalError,
+    },
+};
+
+use std::{
+    env,
+    fmt,
+    fs,
+    str::FromStr,
+};
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+    Local,
+};
+
+#[cfg(test)]
+mod test;
+
+/// how often the journal records the time actuated so far.
+const HEARTBEAT: f64 = 1.0;
+
+/// what happens on the next execution after an actuation was
+/// left unfinished (Recovery config entry). The interruption is
+/// always counted and logged first.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Policy {
+    /// carry on with the requested actuation
+    #[default]
+    Log,
+    /// actuate for the rest of the unfinished actuation first
+    Resume,
+    /// refuse to actuate until --ack is used on the profile
+    Refuse,
+}
+
+impl FromStr for Policy {
+    type Err = InvalidPolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "log" => Ok(Self::Log),
+            "resume" => Ok(Self::Resume),
+            "refuse" => Ok(Self::Refuse),
+            _ => Err(InvalidPolicyError::new()),
+        }
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Log => "log",
+            Self::Resume => "resume",
+            Self::Refuse => "refuse",
+        })
+    }
+}
+
+/// the record of an actuation in progress. It is written before
+/// the relay is energized, marked recorded before the actuation
+/// is counted and logged and removed after; finding one means the
+/// actuation never finished (power cut, crash, SIGKILL).
+#[derive(Debug, PartialEq, Clone)]
+pub struct Journal {
+    pub started: DateTime<FixedOffset>,
+    pub requested: f64,
+    /// seconds actuated as of the last heartbeat, the relay may
+    /// have been on for up to a heartbeat longer.
+    pub elapsed: f64,
+    pub gpio_pin: u8,
+    /// the actuation was counted and logged, or was about to be,
+    /// and isn't counted again
+    pub recorded: bool,
+}
+
+impl Journal {
+    pub fn new(requested: f64, gpio_pin: u8) -> Self {
+        Self {
+            started: Local::now().fixed_offset(),
+            requested,
+            elapsed: 0.0,
+            gpio_pin,
+            recorded: false,
+        }
+    }
+
+    fn path(id: &str) -> Result<String, DynError> {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        Ok(format!("{var}/{DIR_NAME}/{id}/journal"))
+    }
+
+    /// the profiles unfinished actuation, if any.
+    pub fn read(id: &str) -> Result<Option<Self>, DynError> {
+        let path = Self::path(id)?;
+
+        if !fs::exists(&path)? {
+            return Ok(None);
+        }
+
+        let raw = fs::read_to_string(&path)?;
+        let field = |key: &str| -> Result<&str, MalformedJournalError> {
+            raw
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+                .ok_or(MalformedJournalError::new())
+        };
+
+        Ok(Some(Self {
+            started: DateTime::parse_from_rfc3339(field("Started")?)?,
+            requested: field("Seconds-Requested")?.parse::<f64>()?,
+            elapsed: field("Seconds-Elapsed")?.parse::<f64>()?,
+            gpio_pin: field("GpioPin")?.parse::<u8>()?,
+            recorded: field("Recorded")?.parse::<bool>()?,
+        }))
+    }
+
+    pub fn write(&self, id: &str) -> Result<(), DynError> {
+        let journal = format!(
+r#"Started:{}
+Seconds-Requested:{}
+Seconds-Elapsed:{}
+GpioPin:{}
+Recorded:{}"#,
+            self.started.to_rfc3339(),
+            self.requested,
+            self.elapsed,
+            self.gpio_pin,
+            self.recorded,
+        );
+
+        file::write_atomic(&Self::path(id)?, &journal)
+    }
+
+    /// records the seconds actuated so far, at most once
+    /// per HEARTBEAT to spare the sd card.
+    pub fn heartbeat(&mut self, elapsed: f64, id: &str) -> Result<(), DynError> {
+        if elapsed - self.elapsed < HEARTBEAT {
+            return Ok(());
+        }
+
+        self.elapsed = elapsed;
+        self.write(id)
+    }
+
+    /// seconds left of the unfinished actuation.
+    pub fn remaining(&self) -> f64 {
+        (self.requested - self.elapsed).max(0.0)
+    }
+
+    pub fn clear(id: &str) -> Result<(), DynError> {
+        let path = Self::path(id)?;
+
+        if fs::exists(&path)? {
+            fs::remove_file(&path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    DynError,
+    file,
+    journal::*,
+};
+
+const PROF: &str = "journal_test";
+
+#[test]
+fn journal_test() -> Result<(), DynError> {
+    file::init_dirs(PROF)?;
+
+    assert_eq!(Journal::read(PROF)?, None);
+
+    let mut journal = Journal::new(12.5, 17);
+    journal.write(PROF)?;
+
+    assert_eq!(Journal::read(PROF)?, Some(journal.clone()));
+
+    journal.heartbeat(0.5, PROF)?;
+    assert_eq!(
+        Journal::read(PROF)?.map(|j| j.elapsed),
+        Some(0.0),
+        "heartbeats should be at most once a second",
+    );
+
+    journal.heartbeat(2.5, PROF)?;
+    assert_eq!(
+        Journal::read(PROF)?.map(|j| j.remaining()),
+        Some(10.0),
+    );
+
+    Journal::clear(PROF)?;
+    assert_eq!(Journal::read(PROF)?, None);
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{PROF}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
@@ -0,0 +1,233 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    errors::{
+        InvalidPolicyError,
+        LockedError,
+    },
+    getter_setter::FullConfig,
+    gpio::BackendKind,
+};
+
+use std::{
+    env,
+    fmt,
+    fs::{
+        self,
+        File,
+        TryLockError,
+    },
+    path::Path,
+    str::FromStr,
+};
+
+#[cfg(test)]
+mod test;
+
+/// what an execution does when another execution holds the
+/// lock of its profile or pin (Overlap config entry).
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OverlapPolicy {
+    /// wait for the other execution to finish
+    #[default]
+    Wait,
+    /// don't actuate, only log that the execution was skipped
+    Skip,
+    /// don't actuate and return an error
+    Fail,
+}
+
+impl FromStr for OverlapPolicy {
+    type Err = InvalidPolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wait" => Ok(Self::Wait),
+            "skip" => Ok(Self::Skip),
+            "fail" => Ok(Self::Fail),
+            _ => Err(InvalidPolicyError::new()),
+        }
+    }
+}
+
+impl fmt::Display for OverlapPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Wait => "wait",
+            Self::Skip => "skip",
+            Self::Fail => "fail",
+        })
+    }
+}
+
+/// advisory locks on a profile directory and the pin it drives,
+/// held until dropped. Every process actuating takes the profile
+/// lock before the pin lock so two executions can't deadlock.
+#[derive(Debug)]
+pub struct Locks {
+    _prof: File,
+    _pin: File,
+}
+
+impl Locks {
+    /// takes the locks of the profile according to its overlap
+    /// policy, Ok(None) means the execution has to be skipped.
+    pub fn acquire(
+        config: &FullConfig,
+        prof: &str,
+    ) -> Result<Option<Self>, DynError> {
+        Self::with_policy(config, prof, config.overlap)
+    }
+
+    /// takes the locks of the profile only if no execution holds 
+    /// either of them, whatever the overlap policy.
+    pub fn try_acquire(
+        config: &FullConfig,
+        prof: &str,
+    ) -> Result<Option<Self>, DynError> {
+        Self::with_policy(config, prof, OverlapPolicy::Skip)
+    }
+
+    fn with_policy(
+        config: &FullConfig,
+        prof: &str,
+        policy: OverlapPolicy,
+    ) -> Result<Option<Self>, DynError> {
+        let dir = {
+            let var = env::var("XDG_CONFIG_HOME")?;
+            format!("{var}/{DIR_NAME}")
+        };
+
+        let Some(prof_lock) = lock(
+            format!("{dir}/{prof}/lock"),
+            policy,
+        )? else {
+            return Ok(None);
+        };
+
+        let Some(pin_lock) = PinLock::with_policy(config, policy)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self { _prof: prof_lock, _pin: pin_lock._file }))
+    }
+}
+
+/// the lock of the pin alone, taken to drive a pin off without
+/// cutting short an execution of another profile sharing it.
+#[derive(Debug)]
+pub struct PinLock {
+    _file: File,
+}
+
+impl PinLock {
+    /// takes the pin lock only if no execution holds it.
+    pub fn try_acquire(config: &FullConfig) -> Result<Option<Self>, DynError> {
+        Self::with_policy(config, OverlapPolicy::Skip)
+    }
+
+    fn with_policy(
+        config: &FullConfig,
+        policy: OverlapPolicy,
+    ) -> Result<Option<Self>, DynError> {
+        let dir = {
+            let var = env::var("XDG_CONFIG_HOME")?;
+            format!("{var}/{DIR_NAME}/.locks")
+        };
+
+        fs::create_dir_all(&dir)?;
+
+        Ok(lock(format!("{dir}/{}.lock", pin_id(config)), policy)?
+            .map(|file| Self { _file: file })
+        )
+    }
+}
+
+/// serializes every read-modify-write of a profiles config and
+/// log between setters and executions. Unlike Locks it is only 
+/// held for the write, and is always waited for.
+#[derive(Debug)]
+pub struct WriteLock {
+    _file: File,
+}
+
+impl WriteLock {
+    pub fn acquire(prof: &str) -> Result<Self, DynError> {
+        let path = {
+            let var = env::var("XDG_CONFIG_HOME")?;
+            format!("{var}/{DIR_NAME}/{prof}/write.lock")
+        };
+
+        Self::open(path)
+    }
+
+    /// the WriteLock of the relay registry, which is shared
+    /// by every profile.
+    pub fn registry() -> Result<Self, DynError> {
+        let dir = {
+            let var = env::var("XDG_CONFIG_HOME")?;
+            format!("{var}/{DIR_NAME}/.locks")
+        };
+
+        fs::create_dir_all(&dir)?;
+        Self::open(format!("{dir}/relays.lock"))
+    }
+
+    fn open(path: String) -> Result<Self, DynError> {
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?
+        ;
+
+        file.lock()?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// names the physical pin a profile drives. A pin may be shared
+/// by several profiles of the same backend.
+pub fn pin_id(config: &FullConfig) -> String {
+    match config.backend {
+        BackendKind::Cdev => {
+            let chip = Path::new(&config.gpio_chip)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("gpiochip")
+            ;
+
+            format!("{chip}-line-{}", config.gpio_line)
+        }
+
+        BackendKind::Rppal | BackendKind::Sim => {
+            format!("pin-{}", config.gpio_pin)
+        }
+    }
+}
+
+fn lock(
+    path: String,
+    policy: OverlapPolicy,
+) -> Result<Option<File>, DynError> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?
+    ;
+
+    if policy == OverlapPolicy::Wait {
+        file.lock()?;
+        return Ok(Some(file));
+    }
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => match policy {
+            OverlapPolicy::Fail => Err(LockedError::new().into()),
+            _ => Ok(None),
+        },
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
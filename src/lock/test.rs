@@ -0,0 +1,55 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    file,
+    getter_setter::*,
+    lock::*,
+};
+
+use std::{
+    env,
+    fs,
+};
+
+const PROF: &str = "lock_test";
+
+fn config(overlap: OverlapPolicy) -> Result<FullConfig, DynError> {
+    file::init_dirs(PROF)?;
+
+    RelayCount::set(String::from("0"), PROF)?;
+    RelayMax::set(String::from("100"), PROF)?;
+    MaxTime::set(String::from("5"), PROF)?;
+    GpioPin::set(String::from("200"), PROF)?;
+    Timezone::set(String::from("UTC"), PROF)?;
+    Multiplier::set(String::from("1.0"), PROF)?;
+    GpioBackend::set(String::from("sim"), PROF)?;
+    Overlap::set(overlap.to_string(), PROF)?;
+
+    FullConfig::new(PROF)
+}
+
+#[test]
+fn locks_test() -> Result<(), DynError> {
+    let held = Locks::acquire(&config(OverlapPolicy::Wait)?, PROF)?;
+    assert!(held.is_some());
+
+    assert!(
+        Locks::acquire(&config(OverlapPolicy::Skip)?, PROF)?.is_none(),
+        "acquired a lock which is already held",
+    );
+    assert!(Locks::acquire(&config(OverlapPolicy::Fail)?, PROF).is_err());
+
+    drop(held);
+
+    assert!(
+        Locks::acquire(&config(OverlapPolicy::Fail)?, PROF)?.is_some(),
+        "the locks were not released on drop",
+    );
+
+    let path = {
+        let var = env::var("XDG_CONFIG_HOME")?;
+        format!("{var}/{DIR_NAME}/{PROF}")
+    };
+
+    Ok(fs::remove_dir_all(path)?)
+}
@@ -3,18 +3,59 @@
 #![warn(clippy::unwrap_used)]
 
 pub mod arg;
+pub mod calibrate;
 pub mod errors;
+pub mod expr;
+pub mod forecast;
 pub mod getter_setter;
 pub mod file;
 pub mod gpio;
 pub mod help;
+pub mod journal;
+pub mod lock;
+pub mod record;
+pub mod reconcile;
+pub mod relay;
+pub mod unit;
+pub mod wear;
+
+#[cfg(test)]
+mod fixture;
 
 use crate::{
     getter_setter::{
         FullConfig,
-        Config,
         RelayCount,
     },
+    errors::{
+        ActuationInterruptedError,
+        BusyError,
+        InvalidDurationError,
+        InvalidMaxTimeError,
+        UnacknowledgedInterruptionError,
+    },
+    gpio::{
+        Actuation,
+        Backend,
+        Outcome,
+        Signals,
+        sim::{
+            Sim,
+            Transition,
+        },
+    },
+    journal::{
+        Journal,
+        Policy,
+    },
+    lock::{
+        Locks,
+        PinLock,
+    },
+    unit::{
+        Dose,
+        Request,
+    },
     file as fs,
 };
 
@@ -29,7 +70,11 @@ pub type DynError = Box<dyn std::error::Error>;
 pub const DIR_NAME: &'static str = ".Actuators"; 
 
 fn main() -> Result<(), DynError> {
-    let mut args = env::args();
+    let mut args = env::args()
+        .collect::<Vec<String>>()
+        .into_iter()
+        .peekable()
+    ;
     let _ = args.next();
 
     Ok(while let Some(arg) = args.next() {
@@ -40,22 +85,316 @@ fn main() -> Result<(), DynError> {
 impl FullConfig {
     pub fn execute(
         config: Self,
-        time_on: f64,
+        dose: Dose,
         prof: &str,
     ) -> Result<(), DynError> {
-        let filtered = time_on * config.multiplier
-            .clamp(0.0, config.max_time)
-        ; 
+        let mut backend = gpio::backend(&config, prof)?;
+        Self::execute_with(config, dose, prof, backend.as_mut())
+    }
 
-        gpio::actuator(Duration::from_secs_f64(filtered), &config)?;
+    /// same as execute but actuates through the given backend
+    /// instead of the one selected by the profile.
+    ///
+    /// termination signals received while the relay is on cut the 
+    /// actuation short, it is still counted and logged with the 
+    /// seconds actually actuated before the error is returned.
+    ///
+    /// executions of the same profile or pin never overlap, the 
+    /// profiles overlap policy decides what happens instead.
+    pub fn execute_with(
+        mut config: Self,
+        dose: Dose,
+        prof: &str,
+        backend: &mut dyn Backend,
+    ) -> Result<(), DynError> {
+        // a dose which can't be actuated fails before anything else
+        let request = config.request(dose)?;
+        wear::check(&config, prof)?;
 
-        RelayCount::set(
-            (&config.relay_count + 1).to_string(), 
-            prof,
-        )?;
+        let Some(_locks) = Locks::acquire(&config, prof)? else {
+            println!("Profile = {prof}: busy, execution skipped");
+            return fs::log_skipped(&config, dose, prof);
+        };
+
+        let signals = Signals::catch()?;
 
-        fs::log(&config, filtered, prof)?;
+        config.recover(prof, backend, &signals)?;
+
+        if request.clamped() {
+            println!(
+                "Profile = {prof}: {} seconds requested, capped to the max time of {}",
+                request.requested,
+                config.max_time,
+            );
+        }
+
+        config.actuate(request, prof, backend, &signals)?;
 
         Ok(())
     }
+
+    /// actuates for exactly the given seconds, the multiplier is 
+    /// left out. Used by --calibrate, the actuation is counted and
+    /// logged like any other; an execution holding the profile or 
+    /// its pin is an error whatever the overlap policy.
+    pub fn actuate_exactly(
+        &mut self,
+        seconds: f64,
+        prof: &str,
+        backend: &mut dyn Backend,
+    ) -> Result<Actuation, DynError> {
+        let Some(_locks) = Locks::acquire(self, prof)? else {
+            return Err(BusyError::new().into());
+        };
+
+        let signals = Signals::catch()?;
+
+        let seconds = self.cap(seconds)?;
+        wear::check(self, prof)?;
+
+        self.recover(prof, backend, &signals)?;
+        self.actuate(Request::exactly(seconds), prof, backend, &signals)
+    }
+
+    /// one journaled, counted and logged actuation.
+    fn actuate(
+        &mut self,
+        request: Request,
+        prof: &str,
+        backend: &mut dyn Backend,
+        signals: &Signals,
+    ) -> Result<Actuation, DynError> {
+        let mut journal = Journal::new(request.seconds, self.gpio_pin);
+        journal.write(prof)?;
+
+        let actuation = gpio::actuator_with(
+            Duration::from_secs_f64(request.seconds), 
+            backend,
+            self.polarity,
+            signals.stop(),
+            &mut |elapsed| journal.heartbeat(elapsed, prof),
+        )?;
+
+        // marked as recorded before it is counted, a crash before
+        // the journal is cleared must not count it a second time
+        journal.elapsed = actuation.seconds;
+        journal.recorded = true;
+        journal.write(prof)?;
+
+        self.count_actuation(prof)?;
+        fs::log(self, &actuation, &request, prof)?;
+        Journal::clear(prof)?;
+
+        if actuation.outcome == Outcome::Interrupted {
+            return Err(ActuationInterruptedError::new().into());
+        }
+
+        Ok(actuation)
+    }
+
+    /// deals with an actuation a previous run left unfinished 
+    /// according to the profiles recovery policy.
+    fn recover(
+        &mut self,
+        prof: &str,
+        backend: &mut dyn Backend,
+        signals: &Signals,
+    ) -> Result<(), DynError> {
+        let Some(journal) = Journal::read(prof)? else {
+            return Ok(());
+        };
+
+        let remaining = journal.remaining();
+
+        if !journal.recorded {
+            self.record_interruption(journal, prof)?;
+        }
+
+        match self.recovery {
+            Policy::Log => Journal::clear(prof),
+
+            Policy::Resume => {
+                Journal::clear(prof)?;
+
+                if remaining > 0.0 {
+                    self.actuate(Request::exactly(remaining), prof, backend, signals)?;
+                }
+
+                Ok(())
+            }
+
+            Policy::Refuse => {
+                Err(UnacknowledgedInterruptionError::new().into())
+            }
+        }
+    }
+
+    /// counts and logs an unfinished actuation, the relay did switch.
+    fn record_interruption(
+        &mut self,
+        mut journal: Journal,
+        prof: &str,
+    ) -> Result<(), DynError> {
+        journal.recorded = true;
+        journal.write(prof)?;
+
+        self.count_actuation(prof)?;
+        fs::log_interrupted(self, &journal, prof)
+    }
+
+    /// records the profiles unfinished actuation, if any, and allows
+    /// actuating again under the refuse recovery policy. Returns 
+    /// whether there was anything to acknowledge.
+    pub fn acknowledge(&mut self, prof: &str) -> Result<bool, DynError> {
+        let Some(journal) = Journal::read(prof)? else {
+            return Ok(false);
+        };
+
+        if !journal.recorded {
+            self.record_interruption(journal, prof)?;
+        }
+
+        Journal::clear(prof)?;
+        Ok(true)
+    }
+
+    /// counts on the config file (or the relay registry) rather than
+    /// self, another process may have changed the count since the 
+    /// config was read. Wear thresholds passed by the count are 
+    /// warned about.
+    fn count_actuation(&mut self, prof: &str) -> Result<(), DynError> {
+        self.relay_count = match &self.relay {
+            Some(name) => relay::increment(name)?,
+            None => RelayCount::increment(prof)?,
+        };
+
+        wear::warn(self, self.relay_count - 1, prof)
+    }
+
+    /// rehearses an execution on a simulated pin. The transitions 
+    /// are printed once it is over and appended to the profiles 
+    /// sim_trace file, the relay count and the log are left alone
+    /// since nothing was actuated.
+    pub fn simulate(
+        config: Self,
+        dose: Dose,
+        prof: &str,
+    ) -> Result<Vec<Transition>, DynError> {
+        let request = config.request(dose)?;
+        let mut sim = Sim::with_trace_file(config.gpio_pin, prof)?;
+        let signals = Signals::catch()?;
+
+        if request.clamped() {
+            println!(
+                "Profile = {prof}: {} seconds requested, capped to the max time of {}",
+                request.requested,
+                config.max_time,
+            );
+        }
+
+        gpio::actuator(
+            Duration::from_secs_f64(request.seconds), 
+            &mut sim,
+            config.polarity,
+            signals.stop(),
+        )?;
+
+        for transition in sim.trace() {
+            println!("sim: {transition}");
+        }
+
+        Ok(sim.trace().to_vec())
+    }
+
+    /// drives the profiles pin to its off level, used by systemd
+    /// after every actuation in case the actuation did not. A pin 
+    /// another execution holds (ex. of a profile sharing it) is 
+    /// left alone, that execution turns it off itself. Returns 
+    /// whether the pin was driven off.
+    pub fn force_off(&self, prof: &str) -> Result<bool, DynError> {
+        let Some(_lock) = PinLock::try_acquire(self)? else {
+            return Ok(false);
+        };
+
+        self.drive_off(prof)?;
+        Ok(true)
+    }
+
+    fn drive_off(&self, prof: &str) -> Result<(), DynError> {
+        let mut backend = gpio::backend(self, prof)?;
+        self.polarity.off(backend.as_mut())
+    }
+
+    /// drives the pin off and logs whether the previous actuation
+    /// was interrupted before it could finish. An interrupted 
+    /// actuation is counted and logged here, the journal is kept
+    /// for the next execution unless the recovery policy is log.
+    /// Returns whether it was interrupted, None if an execution 
+    /// holds the profile or its pin and nothing was touched.
+    pub fn safe_state(&mut self, prof: &str) -> Result<Option<bool>, DynError> {
+        let Some(_locks) = Locks::try_acquire(self, prof)? else {
+            return Ok(None);
+        };
+
+        self.drive_off(prof)?;
+
+        let journal = Journal::read(prof)?;
+        let interrupted = journal.is_some();
+
+        if let Some(journal) = journal {
+            if !journal.recorded {
+                self.record_interruption(journal, prof)?;
+            }
+
+            if self.recovery == Policy::Log {
+                Journal::clear(prof)?;
+            }
+        }
+
+        fs::log_safe_state(self, interrupted, prof)?;
+        Ok(Some(interrupted))
+    }
+
+    /// the seconds actuated for the given dose. A time is multiplied
+    /// by the multiplier, an amount is looked up on the calibration 
+    /// curve if the profile has one and divided by the flow rate 
+    /// otherwise. Whatever it comes to is capped to the max time, 
+    /// seconds which aren't a number, are infinite or negative are 
+    /// an error.
+    fn request(&self, dose: Dose) -> Result<Request, DynError> {
+        let (amount, requested) = match dose {
+            Dose::Time(time_on) => {
+                if !time_on.is_finite() || time_on < 0.0 {
+                    return Err(InvalidDurationError::new(time_on).into());
+                }
+
+                (None, time_on * self.multiplier)
+            }
+
+            Dose::Amount(amount) => {
+                let seconds = match &self.curve {
+                    Some(curve) => curve.seconds(amount.in_unit(self.unit)),
+                    None => amount.seconds(self.unit, self.flow_rate)?,
+                };
+
+                (Some(amount), seconds)
+            }
+        };
+
+        Ok(Request { amount, requested, seconds: self.cap(requested)? })
+    }
+
+    /// seconds capped to the max time, this is the limit every 
+    /// actuation goes through.
+    fn cap(&self, seconds: f64) -> Result<f64, DynError> {
+        if !self.max_time.is_finite() || self.max_time < 0.0 {
+            return Err(InvalidMaxTimeError::new().into());
+        }
+
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(InvalidDurationError::new(seconds).into());
+        }
+
+        Ok(seconds.min(self.max_time))
+    }
 }
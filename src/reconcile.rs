@@ -0,0 +1,206 @@
+use crate::{
+    DynError,
+    errors::{
+        InvalidConfigError,
+        InvalidRepairError,
+    },
+    file,
+    getter_setter::{
+        Config,
+        FullConfig,
+        RelayCount,
+    },
+    record::{
+        self,
+        Log,
+        Malformed,
+        Record,
+    },
+    relay,
+};
+
+use std::str::FromStr;
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+    NaiveDate,
+    NaiveTime,
+};
+
+use toml_edit::Datetime;
+
+#[cfg(test)]
+mod test;
+
+/// the actuations a log accounts for.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Tally {
+    pub actuations: i64,
+    pub malformed: Vec<Malformed>,
+    /// the lines of each legacy log backup which were never
+    /// converted, by the backups file name
+    pub legacy: Vec<(String, Malformed)>,
+}
+
+/// counts the actuations recorded in the log since the relay was
+/// installed (all of them if that isn't known), see
+/// Record::actuations. Entries of a legacy log which couldn't be
+/// converted still count, when they were logged can't always be
+/// read so those count whenever it was. The lines which can't be
+/// read are reported along with the count.
+pub fn tally(log: &Log, since: Option<DateTime<FixedOffset>>) -> Tally {
+    // actuations of the relays this one replaced are left out
+    let counts = |timestamp: &DateTime<FixedOffset>| since.is_none_or(|since| *timestamp >= since);
+
+    let logged = log.records
+        .iter()
+        .filter(|record| counts(&record.timestamp))
+        .map(Record::actuations)
+        .sum::<i64>()
+    ;
+
+    let unconverted = log.legacy
+        .iter()
+        .flat_map(|unconverted| &unconverted.actuations)
+        .filter(|timestamp| timestamp.as_ref().is_none_or(counts))
+        .count()
+    ;
+
+    Tally {
+        actuations: logged + unconverted as i64,
+        malformed: log.malformed.clone(),
+        legacy: log.legacy
+            .iter()
+            .flat_map(|unconverted| unconverted.malformed
+                .iter()
+                .map(|malformed| (unconverted.backup.clone(), malformed.clone()))
+            )
+            .collect(),
+    }
+}
+
+/// the relay count of a profile next to what its log (or the logs
+/// of every profile sharing its relay) accounts for.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Reconciliation {
+    pub count: u64,
+    pub logged: i64,
+    /// the malformed lines of each profiles log
+    pub malformed: Vec<(String, Malformed)>,
+    /// the lines of each profiles legacy log backups which were
+    /// never converted, by profile and backup
+    pub legacy: Vec<(String, String, Malformed)>,
+}
+
+impl Reconciliation {
+    /// actuations counted but never logged, negative if the
+    /// log has more than were counted.
+    pub fn difference(&self) -> i64 {
+        self.count as i64 - self.logged
+    }
+}
+
+pub fn reconcile(config: &FullConfig, prof: &str) -> Result<Reconciliation, DynError> {
+    let since = config.relay_installed.map(since).transpose()?;
+
+    let profs = match &config.relay {
+        Some(name) => relay::profiles(name)?,
+        None => vec![prof.to_string()],
+    };
+
+    let mut reconciliation = Reconciliation {
+        count: config.relay_count,
+        logged: 0,
+        malformed: Vec::new(),
+        legacy: Vec::new(),
+    };
+
+    for prof in profs {
+        let tally = tally(&record::read(&prof)?, since);
+
+        reconciliation.logged += tally.actuations;
+        reconciliation.malformed.extend(
+            tally.malformed.into_iter().map(|malformed| (prof.clone(), malformed))
+        );
+        reconciliation.legacy.extend(
+            tally.legacy.into_iter().map(|(backup, malformed)| (prof.clone(), backup, malformed))
+        );
+    }
+
+    Ok(reconciliation)
+}
+
+/// when the relay was installed. A date without a time is taken 
+/// as the start of that day and a time without an offset as UTC,
+/// a time without a date is an error rather than counting the 
+/// actuations of every relay the profile went through.
+pub fn since(installed: Datetime) -> Result<DateTime<FixedOffset>, DynError> {
+    let invalid = || InvalidConfigError::new(format!(
+        "relay.installed = {installed} is not a date, ex. 2026-10-18 or 2026-10-18T08:00:00+02:00"
+    ));
+
+    if installed.offset.is_some() {
+        return Ok(DateTime::parse_from_rfc3339(&installed.to_string())?);
+    }
+
+    let date = installed.date.ok_or_else(invalid)?;
+    let date = NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+        .ok_or_else(invalid)?
+    ;
+
+    let time = match installed.time {
+        Some(time) => NaiveTime::from_hms_opt(
+            time.hour.into(), 
+            time.minute.into(), 
+            time.second.unwrap_or(0).into(),
+        ).ok_or_else(invalid)?,
+        None => NaiveTime::MIN,
+    };
+
+    Ok(date.and_time(time).and_utc().fixed_offset())
+}
+
+/// which side --reconcile makes agree with the other.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Repair {
+    /// sets the relay count to what the log accounts for
+    Count,
+    /// logs a reconciled record accounting for the difference
+    Log,
+}
+
+impl FromStr for Repair {
+    type Err = InvalidRepairError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "count" => Ok(Self::Count),
+            "log" => Ok(Self::Log),
+            _ => Err(InvalidRepairError::new()),
+        }
+    }
+}
+
+/// makes the relay count and the log agree, a relay shared
+/// through the registry has its count set, and the record is
+/// logged to the profile reconciled.
+pub fn repair(
+    config: &FullConfig,
+    prof: &str,
+    reconciliation: &Reconciliation,
+    repair: Repair,
+) -> Result<(), DynError> {
+    match repair {
+        Repair::Count => {
+            let count = u64::try_from(reconciliation.logged.max(0))?;
+
+            match &config.relay {
+                Some(name) => relay::set_count(name, count),
+                None => RelayCount::set(count.to_string(), prof),
+            }
+        }
+
+        Repair::Log => file::log_reconciled(config, reconciliation.difference(), prof),
+    }
+}
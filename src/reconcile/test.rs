@@ -0,0 +1,142 @@
+use crate::{
+    DynError,
+    fixture::{
+        cleanup,
+        execute,
+        setup,
+    },
+    getter_setter::*,
+    gpio::Outcome,
+    lock::OverlapPolicy,
+    reconcile::*,
+    record::{
+        Event,
+        legacy,
+    },
+    unit::Dose,
+};
+
+fn line(event: Event, timestamp: &str) -> Result<String, DynError> {
+    Ok(serde_json::to_string(&Record {
+        profile: String::from("reconcile_test"),
+        pin: 17,
+        timestamp: DateTime::parse_from_rfc3339(timestamp)?,
+        event,
+    })?)
+}
+
+fn entry() -> Event {
+    Event::Entry {
+        requested_seconds: 1.0,
+        actual_seconds: 1.0,
+        clamped: false,
+        outcome: Outcome::Completed,
+        requested_amount: None,
+        multiplier: 1.0,
+        max_time: 30.0,
+    }
+}
+
+#[test]
+fn tally_test() -> Result<(), DynError> {
+    let log = [
+        line(entry(), "2026-10-17T08:00:00+00:00")?,
+        line(Event::Skipped { requested: Dose::Time(1.0), overlap: OverlapPolicy::Skip }, "2026-10-17T08:00:01+00:00")?,
+        String::from("half a line"),
+        line(Event::Reconciled { adjustment: 3, relay_count: 5 }, "2026-10-18T08:00:00+00:00")?,
+        String::new(),
+        line(entry(), "2026-10-18T10:00:00+00:00")?,
+        String::from(r#"{"profile":"reconcile_test","pin":17,"#),
+    ].join("\n");
+
+    let counted = tally(&Log::parse(&log), None);
+    assert_eq!(counted.actuations, 5);
+    assert_eq!(
+        counted.malformed.iter().map(|m| m.line).collect::<Vec<usize>>(),
+        vec![3, 7],
+        "{:?}", counted.malformed,
+    );
+
+    // a relay installed since only accounts for what came after
+    let since = DateTime::parse_from_rfc3339("2026-10-18T00:00:00+00:00")?;
+    assert_eq!(tally(&Log::parse(&log), Some(since)).actuations, 4);
+
+    // entries of a legacy log which couldn't be converted count,
+    // unless they were logged before the relay was installed
+    let raw = "\
+[ENTRY]
+Seconds-Actuated:2
+Timestamp:yesterday
+[ENTRY]
+Seconds-Actuated:2
+Timestamp:Fri, 16 Oct 2026 08:00:00 +0000
+[WEAR]
+Timestamp:Fri, 16 Oct 2026 08:00:01 +0000";
+
+    let log = Log {
+        legacy: vec![legacy::unconverted(raw, "reconcile_test")],
+        ..Log::parse(&log)
+    };
+
+    let counted = tally(&log, None);
+    assert_eq!(counted.actuations, 7);
+    assert_eq!(
+        counted.legacy.iter().map(|(_, m)| m.line).collect::<Vec<usize>>(),
+        vec![1, 4, 7],
+    );
+    assert_eq!(tally(&log, Some(since)).actuations, 5);
+
+    Ok(())
+}
+
+#[test]
+fn repair_test() -> Result<(), DynError> {
+    let prof = "reconcile_test_repair";
+    setup(prof)?;
+
+    execute(prof)?;
+    execute(prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let reconciliation = reconcile(&config, prof)?;
+    assert_eq!((reconciliation.count, reconciliation.logged), (2, 2));
+    assert!(reconciliation.malformed.is_empty());
+
+    // counted by hand, the log has no record of them
+    RelayCount::set(String::from("5"), prof)?;
+
+    let config = FullConfig::new(prof)?;
+    let reconciliation = reconcile(&config, prof)?;
+    assert_eq!(reconciliation.difference(), 3);
+
+    repair(&config, prof, &reconciliation, Repair::Log)?;
+    assert_eq!(reconcile(&config, prof)?.difference(), 0);
+    assert_eq!(FullConfig::new(prof)?.relay_count, 5);
+
+    // the log is taken over the count
+    RelayCount::set(String::from("1"), prof)?;
+
+    let config = FullConfig::new(prof)?;
+    repair(&config, prof, &reconcile(&config, prof)?, Repair::Count)?;
+    assert_eq!(FullConfig::new(prof)?.relay_count, 5);
+
+    assert!("both".parse::<Repair>().is_err());
+
+    cleanup(prof)
+}
+
+#[test]
+fn since_test() -> Result<(), DynError> {
+    let since = |installed: &str| -> Result<String, DynError> {
+        Ok(since(installed.parse()?)?.to_rfc3339())
+    };
+
+    assert_eq!(since("2026-10-18T08:00:00+02:00")?, "2026-10-18T08:00:00+02:00");
+    assert_eq!(since("2026-10-18")?, "2026-10-18T00:00:00+00:00");
+    assert_eq!(since("2026-10-18T08:30:00")?, "2026-10-18T08:30:00+00:00");
+
+    let err = since("08:00:00").err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("relay.installed = 08:00:00 is not a date"), "{err}");
+
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    gpio::Outcome,
+    journal::Policy,
+    lock::{
+        OverlapPolicy,
+        WriteLock,
+    },
+    unit::{
+        Amount,
+        Dose,
+    },
+};
+
+use std::{
+    env,
+    fmt::{
+        self,
+        Display,
+    },
+    fs::{
+        self,
+        OpenOptions,
+    },
+    io::{
+        Read,
+        Seek,
+        SeekFrom,
+        Write,
+    },
+    str::FromStr,
+};
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+};
+
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+#[cfg(test)]
+mod test;
+
+pub mod legacy;
+
+use legacy::Unconverted;
+
+/// one line of a profiles log, ex.
+/// {"profile":"pump","pin":17,"timestamp":"2026-10-18T08:00:00+02:00",
+/// "kind":"entry","requested_seconds":2.0,"actual_seconds":2.0,...}
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub profile: String,
+    /// the gpio pin the profile drove
+    pub pin: u8,
+    #[serde(serialize_with = "rfc3339", deserialize_with = "parsed")]
+    pub timestamp: DateTime<FixedOffset>,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// what a record is about, written as its "kind".
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Event {
+    /// a finished actuation, or one cut short by a signal
+    Entry {
+        /// seconds the dose came to, before they were capped
+        /// to the max time
+        requested_seconds: f64,
+        /// seconds the relay was energized for
+        actual_seconds: f64,
+        /// requested_seconds was capped to the max time
+        clamped: bool,
+        outcome: Outcome,
+        /// the amount asked for when dosing by amount
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "shown_amount",
+            deserialize_with = "parsed_amount",
+        )]
+        requested_amount: Option<Amount>,
+        multiplier: f64,
+        max_time: f64,
+    },
+    /// an actuation which never finished (ex. power cut), the
+    /// relay was on for at least actual_seconds
+    Interrupted {
+        requested_seconds: f64,
+        actual_seconds: f64,
+        #[serde(serialize_with = "rfc3339", deserialize_with = "parsed")]
+        started: DateTime<FixedOffset>,
+        #[serde(serialize_with = "shown", deserialize_with = "parsed")]
+        recovery: Policy,
+    },
+    /// the pin was put into its off state, previous is how the
+    /// actuation before it ended
+    SafeState {
+        previous: Outcome,
+    },
+    /// an execution which did not actuate because another one
+    /// held the profile or its pin
+    Skipped {
+        #[serde(serialize_with = "shown", deserialize_with = "parsed")]
+        requested: Dose,
+        #[serde(serialize_with = "shown", deserialize_with = "parsed")]
+        overlap: OverlapPolicy,
+    },
+    /// the relay passed one of its wear thresholds
+    Wear {
+        percent_used: f64,
+        relay_count: u64,
+        relay_max: u64,
+    },
+    /// an execution of a worn out relay, action is what the wear
+    /// policy made of it (refused, warned or overridden)
+    WornOut {
+        action: String,
+        relay_count: u64,
+        relay_max: u64,
+    },
+    /// a --reconcile, adjustment is the number of actuations
+    /// counted but never logged (negative if more were logged
+    /// than counted)
+    Reconciled {
+        adjustment: i64,
+        relay_count: u64,
+    },
+}
+
+impl Record {
+    /// the actuations the record adds to the relay count.
+    pub fn actuations(&self) -> i64 {
+        match self.event {
+            Event::Entry { .. } | Event::Interrupted { .. } => 1,
+            Event::Reconciled { adjustment, .. } => adjustment,
+            _ => 0,
+        }
+    }
+}
+
+/// a line of a log which can't be read, line counts from 1.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Malformed {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for Malformed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// the records of a log in the order they were written, along
+/// with the lines which couldn't be read and what of each legacy
+/// log couldn't be converted.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Log {
+    pub records: Vec<Record>,
+    pub malformed: Vec<Malformed>,
+    pub legacy: Vec<Unconverted>,
+}
+
+impl Log {
+    pub fn parse(raw: &str) -> Self {
+        let mut log = Self::default();
+
+        for (idx, line) in raw.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<Record>(line) {
+                Ok(record) => log.records.push(record),
+                Err(e) => log.malformed.push(Malformed {
+                    line: idx + 1,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        log
+    }
+}
+
+fn path(id: &str) -> Result<String, DynError> {
+    let var = env::var("XDG_CONFIG_HOME")?;
+    Ok(format!("{var}/{DIR_NAME}/{id}/log"))
+}
+
+/// the profiles log, empty if nothing was logged yet. A log
+/// written before records were JSON lines is converted first
+/// (see legacy::convert).
+pub fn read(id: &str) -> Result<Log, DynError> {
+    let _lock = WriteLock::acquire(id)?;
+    legacy::convert(id)?;
+
+    let path = path(id)?;
+
+    let mut log = match fs::exists(&path)? {
+        true => Log::parse(&fs::read_to_string(&path)?),
+        false => Log::default(),
+    };
+
+    log.legacy = legacy::read(id)?;
+    Ok(log)
+}
+
+/// adds the record to the end of the profiles log. Nothing
+/// before it is rewritten, a line cut short by a power cut is
+/// ended first so only that line is lost.
+pub fn append(record: &Record, id: &str) -> Result<(), DynError> {
+    let _lock = WriteLock::acquire(id)?;
+    legacy::convert(id)?;
+
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path(id)?)?
+    ;
+
+    if file.seek(SeekFrom::End(0))? > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+
+        if last[0] != b'\n' {
+            line.insert(0, '\n');
+        }
+    }
+
+    file.write_all(line.as_bytes())?;
+    Ok(file.sync_data()?)
+}
+
+fn rfc3339<S>(time: &DateTime<FixedOffset>, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    ser.serialize_str(&time.to_rfc3339())
+}
+
+fn shown<S, T>(value: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Display,
+{
+    ser.collect_str(value)
+}
+
+fn parsed<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let raw = String::deserialize(de)?;
+    raw.parse::<T>().map_err(de::Error::custom)
+}
+
+fn shown_amount<S>(amount: &Option<Amount>, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match amount {
+        Some(amount) => ser.collect_str(amount),
+        None => ser.serialize_none(),
+    }
+}
+
+fn parsed_amount<'de, D>(de: D) -> Result<Option<Amount>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(de)?
+        .map(|raw| raw.parse::<Amount>())
+        .transpose()
+        .map_err(de::Error::custom)
+}
@@ -0,0 +1,271 @@
+use crate::{
+    DynError,
+    file,
+    gpio::Outcome,
+    record::*,
+};
+
+use std::{
+    fs,
+    str::FromStr,
+};
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+};
+
+/// a [RECORD] of a log from before records were JSON lines and
+/// its Key:value lines, ex.
+/// [ENTRY]
+/// Seconds-Actuated:2
+/// Timestamp:Sun, 18 Oct 2026 08:00:00 +0200
+#[derive(Debug, PartialEq)]
+pub struct Block<'a> {
+    pub line: usize,
+    pub kind: &'a str,
+    pub fields: Vec<(usize, &'a str, &'a str)>,
+}
+
+impl Block<'_> {
+    fn field(&self, key: &str) -> Result<(usize, &str), Malformed> {
+        self.fields
+            .iter()
+            .find(|(_, field, _)| *field == key)
+            .map(|(line, _, value)| (*line, *value))
+            .ok_or(Malformed {
+                line: self.line,
+                reason: format!("[{}] has no {key}", self.kind),
+            })
+    }
+
+    fn parse<T: FromStr>(&self, key: &str) -> Result<T, Malformed> {
+        let (line, value) = self.field(key)?;
+
+        value.parse::<T>().map_err(|_| Malformed {
+            line,
+            reason: format!("the {key} {value:?} can't be read"),
+        })
+    }
+
+    fn time(&self, key: &str) -> Result<DateTime<FixedOffset>, Malformed> {
+        let (line, value) = self.field(key)?;
+
+        DateTime::parse_from_rfc2822(value).map_err(|e| Malformed {
+            line,
+            reason: format!("the {key} {value:?} can't be read, {e}"),
+        })
+    }
+
+    /// the block as a record of the profile id.
+    pub fn record(&self, id: &str) -> Result<Record, Malformed> {
+        // [ENTRY] is the only record logs had before they were
+        // JSON lines
+        if self.kind != "ENTRY" {
+            return Err(Malformed {
+                line: self.line,
+                reason: format!("unknown record [{}]", self.kind),
+            });
+        }
+
+        let actual_seconds = self.parse::<f64>("Seconds-Actuated")?;
+
+        // only completed actuations were logged, at the seconds
+        // requested
+        let event = Event::Entry {
+            requested_seconds: actual_seconds,
+            actual_seconds,
+            clamped: false,
+            outcome: Outcome::Completed,
+            requested_amount: None,
+            multiplier: self.parse::<f64>("Multiplier")?,
+            max_time: self.parse::<f64>("Max-Time")?,
+        };
+
+        Ok(Record {
+            profile: id.to_string(),
+            pin: self.parse::<u8>("GpioPin")?,
+            timestamp: self.time("Timestamp")?,
+            event,
+        })
+    }
+}
+
+/// splits a legacy log into its records, lines which aren't part
+/// of one are returned as malformed.
+pub fn blocks(raw: &str) -> (Vec<Block<'_>>, Vec<Malformed>) {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut malformed = Vec::new();
+
+    for (idx, line) in raw.lines().enumerate() {
+        let number = idx + 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(kind) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            blocks.push(Block { line: number, kind, fields: Vec::new() });
+            continue;
+        }
+
+        match (blocks.last_mut(), line.split_once(':')) {
+            (Some(block), Some((key, value))) => block.fields.push((number, key, value)),
+            (Some(_), None) => malformed.push(Malformed {
+                line: number,
+                reason: format!("expected Key:value, got {line:?}"),
+            }),
+            (None, _) => malformed.push(Malformed {
+                line: number,
+                reason: format!("{line:?} is not part of a record"),
+            }),
+        }
+    }
+
+    (blocks, malformed)
+}
+
+/// the records of a legacy log and the lines which couldn't be
+/// converted, in the order they were found.
+pub fn records(raw: &str, id: &str) -> (Vec<Record>, Vec<Malformed>) {
+    let (blocks, mut malformed) = blocks(raw);
+    let mut records = Vec::new();
+
+    for block in blocks {
+        match block.record(id) {
+            Ok(record) => records.push(record),
+            Err(e) => malformed.push(e),
+        }
+    }
+
+    malformed.sort_by_key(|malformed| malformed.line);
+    (records, malformed)
+}
+
+/// what of a legacy log never made it into the JSON log, it is
+/// still in the backup it was kept as.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Unconverted {
+    /// the file name of the backup, ex. log.legacy
+    pub backup: String,
+    pub malformed: Vec<Malformed>,
+    /// when each [ENTRY] among them was logged, None if that
+    /// can't be read. They did switch the relay.
+    pub actuations: Vec<Option<DateTime<FixedOffset>>>,
+}
+
+pub fn unconverted(raw: &str, id: &str) -> Unconverted {
+    let (blocks, _) = blocks(raw);
+    let (_, malformed) = records(raw, id);
+
+    let actuations = blocks
+        .iter()
+        .filter(|block| block.kind == "ENTRY")
+        .filter(|block| block.record(id).is_err())
+        .map(|block| block.time("Timestamp").ok())
+        .collect()
+    ;
+
+    Unconverted { backup: String::new(), malformed, actuations }
+}
+
+/// the backups of the log, log.legacy and then log.legacy.1,
+/// log.legacy.2... if it was converted again.
+fn backups(path: &str) -> Result<Vec<String>, DynError> {
+    let base = format!("{path}.legacy");
+
+    let mut backups = Vec::new();
+    let mut backup = base.clone();
+    let mut n = 1;
+
+    while fs::exists(&backup)? {
+        backups.push(backup);
+        backup = format!("{base}.{n}");
+        n += 1;
+    }
+
+    Ok(backups)
+}
+
+/// keeps the legacy log next to the log, as the first of its
+/// backups which doesn't exist yet.
+fn backup(raw: &str, path: &str) -> Result<String, DynError> {
+    let backup = match backups(path)?.len() {
+        0 => format!("{path}.legacy"),
+        n => format!("{path}.legacy.{n}"),
+    };
+
+    file::write_atomic(&backup, raw)?;
+
+    Ok(backup)
+}
+
+fn file_name(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+/// what of each of the profiles legacy log backups wasn't 
+/// converted, nothing if the log never was.
+pub fn read(id: &str) -> Result<Vec<Unconverted>, DynError> {
+    backups(&path(id)?)?
+        .into_iter()
+        .map(|backup| Ok(Unconverted {
+            backup: file_name(&backup),
+            ..unconverted(&fs::read_to_string(&backup)?, id)
+        }))
+        .collect()
+}
+
+/// whether the log is in the [RECORD] format written before
+/// records were JSON lines, it starts with an [ENTRY] and none of
+/// its lines is a JSON record. Anything else is read as JSON 
+/// lines, where lines which aren't records are malformed.
+pub fn is_legacy(raw: &str) -> bool {
+    let entry = raw
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.trim() == "[ENTRY]")
+    ;
+
+    entry && !raw.lines().any(|line| serde_json::from_str::<Record>(line).is_ok())
+}
+
+/// rewrites a legacy log as JSON lines, the old log is kept
+/// next to it as log.legacy (log.legacy.1... if that is taken,
+/// a backup is never overwritten). Lines which can't be converted
+/// are reported and left out, they are still in the backup and 
+/// read along with the log (see Unconverted). The caller holds
+/// the profiles WriteLock.
+pub fn convert(id: &str) -> Result<(), DynError> {
+    let path = path(id)?;
+
+    if !fs::exists(&path)? {
+        return Ok(());
+    }
+
+    let raw = fs::read_to_string(&path)?;
+
+    if !is_legacy(&raw) {
+        return Ok(());
+    }
+
+    let (records, malformed) = records(&raw, id);
+
+    let mut lines = String::new();
+
+    for record in &records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+
+    let backup = file_name(&backup(&raw, &path)?);
+    file::write_atomic(&path, &lines)?;
+
+    println!("Profile = {id}: converted the log to JSON lines, the old log is kept as {backup}");
+
+    for malformed in malformed {
+        println!("Profile = {id}: {backup} {malformed}, it was not converted");
+    }
+
+    Ok(())
+}
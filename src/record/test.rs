@@ -0,0 +1,189 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    file,
+    record::*,
+};
+
+use std::{
+    env,
+    fs,
+};
+
+fn dir(prof: &str) -> Result<String, DynError> {
+    let var = env::var("XDG_CONFIG_HOME")?;
+    Ok(format!("{var}/{DIR_NAME}/{prof}"))
+}
+
+fn record(event: Event) -> Result<Record, DynError> {
+    Ok(Record {
+        profile: String::from("record_test"),
+        pin: 17,
+        timestamp: DateTime::parse_from_rfc3339("2026-10-18T08:00:00+02:00")?,
+        event,
+    })
+}
+
+/// every kind of record reads back as it was written
+#[test]
+fn round_trip_test() -> Result<(), DynError> {
+    let events = [
+        Event::Entry {
+            requested_seconds: 2.5,
+            actual_seconds: 2.0,
+            clamped: true,
+            outcome: Outcome::Interrupted,
+            requested_amount: Some("250ml".parse::<Amount>()?),
+            multiplier: 1.5,
+            max_time: 2.0,
+        },
+        Event::Interrupted {
+            requested_seconds: 4.0,
+            actual_seconds: 1.0,
+            started: DateTime::parse_from_rfc3339("2026-10-18T07:59:59+02:00")?,
+            recovery: Policy::Resume,
+        },
+        Event::SafeState { previous: Outcome::Completed },
+        Event::Skipped { requested: "8floz".parse::<Dose>()?, overlap: OverlapPolicy::Skip },
+        Event::Wear { percent_used: 80.0, relay_count: 8, relay_max: 10 },
+        Event::WornOut { action: String::from("refused"), relay_count: 10, relay_max: 10 },
+        Event::Reconciled { adjustment: -2, relay_count: 3 },
+    ];
+
+    for event in events {
+        let record = record(event)?;
+        let line = serde_json::to_string(&record)?;
+
+        assert!(line.contains(r#""timestamp":"2026-10-18T08:00:00+02:00""#), "{line}");
+        assert_eq!(Log::parse(&line).records, vec![record]);
+    }
+
+    let entry = serde_json::to_string(&record(Event::SafeState { previous: Outcome::Completed })?)?;
+    assert_eq!(
+        entry,
+        r#"{"profile":"record_test","pin":17,"timestamp":"2026-10-18T08:00:00+02:00","kind":"safe-state","previous":"completed"}"#,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn append_test() -> Result<(), DynError> {
+    let prof = "record_test_append";
+    file::init_dirs(prof)?;
+    let path = format!("{}/log", dir(prof)?);
+
+    let safe_state = record(Event::SafeState { previous: Outcome::Completed })?;
+    append(&safe_state, prof)?;
+
+    // a power cut in the middle of a line only loses that line
+    let cut = fs::read_to_string(&path)?;
+    fs::write(&path, format!("{cut}{}", &cut[..20]))?;
+
+    append(&safe_state, prof)?;
+
+    let log = read(prof)?;
+    assert_eq!(log.records, vec![safe_state.clone(), safe_state]);
+    assert_eq!(log.malformed.iter().map(|m| m.line).collect::<Vec<usize>>(), vec![2]);
+
+    Ok(fs::remove_dir_all(dir(prof)?)?)
+}
+
+#[test]
+fn legacy_test() -> Result<(), DynError> {
+    let prof = "record_test_legacy";
+    file::init_dirs(prof)?;
+    let path = format!("{}/log", dir(prof)?);
+
+    // entries as the baseline wrote them, one which can't be
+    // converted and a block which was never written
+    let legacy = "\
+[ENTRY]
+Seconds-Actuated:2
+Timestamp:Sun, 18 Oct 2026 08:00:00 +0200
+GpioPin:17
+Multiplier:1
+Max-Time:30
+[ENTRY]
+Seconds-Actuated:30
+Timestamp:Sun, 18 Oct 2026 09:00:00 +0200
+GpioPin:17
+Multiplier:1.5
+Max-Time:30
+[ENTRY]
+Seconds-Actuated:2
+Timestamp:yesterday
+GpioPin:17
+Multiplier:1
+Max-Time:30
+[WEAR]
+Timestamp:Sun, 18 Oct 2026 10:00:03 +0200
+GpioPin:17";
+
+    fs::write(&path, legacy)?;
+
+    let log = read(prof)?;
+    assert!(log.malformed.is_empty());
+    assert_eq!(log.records.len(), 2, "{log:?}");
+
+    assert_eq!(
+        log.records[0].event,
+        Event::Entry {
+            requested_seconds: 2.0,
+            actual_seconds: 2.0,
+            clamped: false,
+            outcome: Outcome::Completed,
+            requested_amount: None,
+            multiplier: 1.0,
+            max_time: 30.0,
+        },
+    );
+    assert!(matches!(
+        log.records[1].event,
+        Event::Entry { actual_seconds: 30.0, multiplier: 1.5, .. }
+    ));
+    assert_eq!(log.records[0].timestamp.to_rfc3339(), "2026-10-18T08:00:00+02:00");
+    assert_eq!(log.records[0].profile, prof);
+    assert_eq!(log.records.iter().map(Record::actuations).sum::<i64>(), 2);
+
+    // the old log is kept as it was and only converted once
+    assert_eq!(fs::read_to_string(format!("{path}.legacy"))?, legacy);
+    assert!(!legacy::is_legacy(&fs::read_to_string(&path)?));
+
+    append(&record(Event::SafeState { previous: Outcome::Completed })?, prof)?;
+    assert_eq!(read(prof)?.records.len(), 3);
+
+    let (_, malformed) = legacy::records(legacy, prof);
+    assert_eq!(
+        malformed.iter().map(|m| m.line).collect::<Vec<usize>>(),
+        vec![15, 19],
+    );
+
+    // the entry which couldn't be converted is still read
+    let unconverted = read(prof)?.legacy;
+    assert_eq!(unconverted.len(), 1);
+    assert_eq!(unconverted[0].backup, "log.legacy");
+    assert_eq!(unconverted[0].malformed, malformed);
+    assert_eq!(unconverted[0].actuations, vec![None]);
+
+    // a stray line in the JSON log is malformed, the log isn't
+    // taken for a legacy one
+    let json = fs::read_to_string(&path)?;
+    fs::write(&path, format!("[ENTRY]\n{json}"))?;
+
+    let log = read(prof)?;
+    assert_eq!(log.records.len(), 3);
+    assert_eq!(log.malformed.len(), 1);
+    assert_eq!(log.malformed[0].line, 1);
+    assert_eq!(fs::read_to_string(&path)?, format!("[ENTRY]\n{json}"));
+    assert_eq!(fs::read_to_string(format!("{path}.legacy"))?, legacy);
+
+    // a legacy log converted again doesn't overwrite the backup
+    fs::write(&path, legacy)?;
+    assert_eq!(read(prof)?.records.len(), 2);
+    assert_eq!(fs::read_to_string(format!("{path}.legacy"))?, legacy);
+    assert_eq!(fs::read_to_string(format!("{path}.legacy.1"))?, legacy);
+    assert_eq!(read(prof)?.legacy.len(), 2);
+
+    Ok(fs::remove_dir_all(dir(prof)?)?)
+}
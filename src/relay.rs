@@ -0,0 +1,331 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    errors::{
+        InvalidRelayNameError,
+        MalformedConfigError,
+        RelayExistsError,
+        UnknownRelayError,
+    },
+    file,
+    getter_setter::{
+        FullConfig,
+        whole_number,
+    },
+    lock::WriteLock,
+};
+
+use std::{
+    collections::BTreeMap,
+    env,
+    fs,
+};
+
+use chrono::{
+    DateTime,
+    Local,
+    SecondsFormat,
+};
+
+use serde::Deserialize;
+
+use toml_edit::{
+    ArrayOfTables,
+    Datetime,
+    DocumentMut,
+    Item,
+    Table,
+    Value,
+    value,
+};
+
+#[cfg(test)]
+mod test;
+
+/// a physical relay. Profiles which name it (relay.name config
+/// entry) drive its pin and charge their actuations to its count
+/// instead of their own, so a relay shared by several profiles
+/// wears as one.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Relay {
+    pub pin: u8,
+    /// actuations the relay is rated for
+    pub max: u64,
+    #[serde(default)]
+    pub count: u64,
+    pub installed: Option<Datetime>,
+    /// the relays it replaced, oldest first
+    #[serde(default)]
+    pub history: Vec<Retired>,
+}
+
+/// a relay taken out of service by --replace-relay, kept in the
+/// history of the relay (or profile) which replaced it.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Retired {
+    /// actuations it was counted for when it was retired
+    pub count: u64,
+    pub max: u64,
+    /// unknown for relays which were counted before
+    /// install dates were kept
+    pub installed: Option<Datetime>,
+    pub retired: Datetime,
+}
+
+impl Retired {
+    /// days the relay was in service, if it is known when it was
+    /// installed.
+    pub fn days(&self) -> Option<f64> {
+        days_between(self.installed.as_ref()?, &self.retired)
+    }
+
+    /// the entry as written to a history, ex. 
+    /// [[pump.history]] count = 98000, max = 100000, ...
+    pub fn table(&self) -> Result<Table, DynError> {
+        let mut table = Table::new();
+        table.insert("count", value(i64::try_from(self.count)?));
+        table.insert("max", value(i64::try_from(self.max)?));
+
+        if let Some(installed) = self.installed {
+            table.insert("installed", value(installed));
+        }
+
+        table.insert("retired", value(self.retired));
+
+        Ok(table)
+    }
+}
+
+/// days from one date to another, None unless both are
+/// full dates with a time and offset.
+pub fn days_between(from: &Datetime, to: &Datetime) -> Option<f64> {
+    let from = DateTime::parse_from_rfc3339(&from.to_string()).ok()?;
+    let to = DateTime::parse_from_rfc3339(&to.to_string()).ok()?;
+
+    Some((to - from).num_seconds() as f64 / 86400.0)
+}
+
+fn now() -> Result<Datetime, DynError> {
+    Ok(Local::now()
+        .to_rfc3339_opts(SecondsFormat::Secs, false)
+        .parse::<Datetime>()?
+    )
+}
+
+/// the registry is a toml file with a table per relay, ex.
+/// [pump] pin = 17, max = 100000, count = 0, installed = ...
+fn path() -> Result<String, DynError> {
+    let var = env::var("XDG_CONFIG_HOME")?;
+    Ok(format!("{var}/{DIR_NAME}/.relays.toml"))
+}
+
+/// every registered relay by name.
+pub fn registry() -> Result<BTreeMap<String, Relay>, DynError> {
+    let _lock = WriteLock::registry()?;
+    read_locked()
+}
+
+fn read_locked() -> Result<BTreeMap<String, Relay>, DynError> {
+    let path = path()?;
+
+    if !fs::exists(&path)? {
+        return Ok(BTreeMap::new());
+    }
+
+    Ok(toml::from_str(&fs::read_to_string(&path)?)?)
+}
+
+pub fn get(name: &str) -> Result<Relay, DynError> {
+    registry()?
+        .remove(name)
+        .ok_or(UnknownRelayError::new(name.to_string()).into())
+}
+
+/// registers a new relay with a count of 0, installed now.
+pub fn add(name: &str, pin: u8, max: u64) -> Result<(), DynError> {
+    verify_name(name)?;
+
+    update(|doc| {
+        if doc.contains_key(name) {
+            return Err(RelayExistsError::new(name.to_string()).into());
+        }
+
+        doc.insert(name, Item::Table(table(pin, max)?));
+        Ok(())
+    })
+}
+
+/// the table of a fresh relay.
+fn table(pin: u8, max: u64) -> Result<Table, DynError> {
+    let mut table = Table::new();
+    table.insert("pin", value(i64::from(pin)));
+    table.insert("max", value(i64::try_from(max)?));
+    table.insert("count", value(0));
+    table.insert("installed", value(now()?));
+
+    Ok(table)
+}
+
+/// retires the relay and starts counting its replacement from 0,
+/// installed now and rated for max actuations (or as many as the
+/// old one). Returns the retired relay.
+pub fn replace(name: &str, max: Option<u64>) -> Result<Retired, DynError> {
+    let mut retired = None;
+
+    update(|doc| {
+        let relay = doc
+            .get_mut(name)
+            .ok_or(UnknownRelayError::new(name.to_string()))?
+            .as_table_mut()
+            .ok_or(MalformedConfigError::new())?
+        ;
+
+        retired = Some(retire(relay, 0, max)?);
+        Ok(())
+    })?;
+
+    Ok(retired.ok_or(MalformedConfigError::new())?)
+}
+
+/// archives the count, max and install date of the relay in table
+/// (a relay of the registry or the relay section of a profile) to
+/// its history and resets them for a new relay. default_max is the
+/// max of a table which doesn't have one.
+pub fn retire(
+    table: &mut Table,
+    default_max: u64,
+    max: Option<u64>,
+) -> Result<Retired, DynError> {
+    let number = |key: &str| whole_number(table.get(key), &format!("relay.{key}"));
+
+    let retired = Retired {
+        count: number("count")?.unwrap_or(0),
+        max: number("max")?.unwrap_or(default_max),
+        installed: table
+            .get("installed")
+            .and_then(Item::as_datetime)
+            .copied()
+        ,
+        retired: now()?,
+    };
+
+    table
+        .entry("history")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or(MalformedConfigError::new())?
+        .push(retired.table()?)
+    ;
+
+    put(table, "count", Value::from(0));
+    put(table, "max", Value::from(i64::try_from(max.unwrap_or(retired.max))?));
+    put(table, "installed", Value::from(retired.retired));
+
+    Ok(retired)
+}
+
+/// sets key in table, keeping the comments around an existing value.
+fn put(table: &mut Table, key: &str, value: Value) {
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(old) => {
+            let decor = old.decor().clone();
+            *old = value;
+            *old.decor_mut() = decor;
+        }
+
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+/// adds one actuation to the relays count, returns the new count.
+pub fn increment(name: &str) -> Result<u64, DynError> {
+    let mut count = 0;
+
+    update(|doc| {
+        let relay = doc
+            .get_mut(name)
+            .ok_or(UnknownRelayError::new(name.to_string()))?
+            .as_table_mut()
+            .ok_or(MalformedConfigError::new())?
+        ;
+
+        let current = whole_number(relay.get("count"), &format!("{name}.count"))?;
+
+        count = current.unwrap_or(0) + 1;
+        relay.insert("count", value(i64::try_from(count)?));
+
+        Ok(())
+    })?;
+
+    Ok(count)
+}
+
+/// sets the count of the relay, ex. after --reconcile.
+pub fn set_count(name: &str, count: u64) -> Result<(), DynError> {
+    set(name, "count", count)
+}
+
+/// sets the max actuations the relay is rated for.
+pub fn set_max(name: &str, max: u64) -> Result<(), DynError> {
+    set(name, "max", max)
+}
+
+fn set(name: &str, key: &str, number: u64) -> Result<(), DynError> {
+    update(|doc| {
+        let relay = doc
+            .get_mut(name)
+            .ok_or(UnknownRelayError::new(name.to_string()))?
+            .as_table_mut()
+            .ok_or(MalformedConfigError::new())?
+        ;
+
+        put(relay, key, Value::from(i64::try_from(number)?));
+        Ok(())
+    })
+}
+
+/// every profile naming the relay, profiles which can't be
+/// read are left out.
+pub fn profiles(name: &str) -> Result<Vec<String>, DynError> {
+    Ok(file::profiles()?
+        .into_iter()
+        .filter(|prof| FullConfig::new(prof)
+            .is_ok_and(|config| config.relay.as_deref() == Some(name))
+        )
+        .collect()
+    )
+}
+
+/// names end up as toml keys and in messages,
+/// they are kept to letters, digits, - and _.
+pub fn verify_name(name: &str) -> Result<(), InvalidRelayNameError> {
+    match
+        !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        true => Ok(()),
+        false => Err(InvalidRelayNameError::new(name.to_string())),
+    }
+}
+
+/// applies edit to the registry and writes it back while holding
+/// its WriteLock, a missing registry starts out empty.
+fn update(
+    edit: impl FnOnce(&mut DocumentMut) -> Result<(), DynError>,
+) -> Result<(), DynError> {
+    let _lock = WriteLock::registry()?;
+
+    let path = path()?;
+
+    let mut doc = if fs::exists(&path)? {
+        fs::read_to_string(&path)?.parse::<DocumentMut>()?
+    } else {
+        DocumentMut::new()
+    };
+
+    edit(&mut doc)?;
+
+    file::write_atomic(&path, &doc.to_string())
+}
@@ -0,0 +1,175 @@
+use crate::{
+    DIR_NAME,
+    DynError,
+    file,
+    fixture::{
+        self,
+        cleanup,
+        execute,
+    },
+    getter_setter::*,
+    relay::*,
+};
+
+use std::{
+    env,
+    fs,
+};
+
+/// the registry is shared by every test, each uses its own relays
+fn remove(name: &str) -> Result<(), DynError> {
+    update(|doc| {
+        doc.remove(name);
+        Ok(())
+    })
+}
+
+fn setup(prof: &str, relay: &str) -> Result<(), DynError> {
+    fixture::profile(prof)?;
+    RelayName::set(String::from(relay), prof)
+}
+
+#[test]
+fn registry_test() -> Result<(), DynError> {
+    let name = "relay_test_registry";
+    add(name, 22, 500)?;
+
+    let relay = get(name)?;
+    assert_eq!((relay.pin, relay.max, relay.count), (22, 500, 0));
+    assert!(relay.installed.is_some());
+
+    assert_eq!(increment(name)?, 1);
+    assert_eq!(increment(name)?, 2);
+    assert_eq!(get(name)?.count, 2);
+
+    let err = add(name, 23, 10).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("Relay Exists Error"), "{err}");
+    assert_eq!(get(name)?.pin, 22, "a duplicate replaced the relay");
+
+    assert!(get("relay_test_missing").is_err());
+    assert!(increment("relay_test_missing").is_err());
+
+    for invalid in ["", "two words", "a.b", "pump]"] {
+        assert!(add(invalid, 22, 10).is_err(), "{invalid:?} should not be a name");
+    }
+
+    remove(name)
+}
+
+/// profiles sharing a relay wear it as one
+#[test]
+fn shared_test() -> Result<(), DynError> {
+    let name = "relay_test_shared";
+    let (first, second) = ("relay_test_shared_a", "relay_test_shared_b");
+    add(name, 24, 100)?;
+
+    setup(first, name)?;
+    setup(second, name)?;
+
+    let config = FullConfig::new(first)?;
+    assert_eq!(config.gpio_pin, 24);
+    assert_eq!(config.relay_max, 100);
+
+    execute(first)?;
+    execute(second)?;
+
+    assert_eq!(get(name)?.count, 2);
+    assert_eq!(FullConfig::new(first)?.relay_count, 2);
+
+    let own = fs::read_to_string(format!(
+        "{}/{DIR_NAME}/{first}/config",
+        env::var("XDG_CONFIG_HOME")?,
+    ))?;
+    assert!(own.contains("count = 0"), "the profile counted the relay");
+
+    // a pin of its own has to match the relays
+    GpioPin::set(String::from("25"), second)?;
+    let err = FullConfig::new(second).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("Relay Pin Mismatch Error"), "{err}");
+
+    assert!(RelayName::set(String::from("relay_test_missing"), first).is_err());
+
+    // setting the count or max of a profile sets the relays
+    RelayCount::set(String::from("42"), first)?;
+    RelayMax::set(String::from("300"), first)?;
+    assert_eq!((get(name)?.count, get(name)?.max), (42, 300));
+    assert!(fs::read_to_string(format!(
+        "{}/{DIR_NAME}/{first}/config",
+        env::var("XDG_CONFIG_HOME")?,
+    ))?.contains("count = 0"));
+
+    // a count edited into something else is never reset
+    update(|doc| {
+        doc[name]["count"] = toml_edit::value("forty two");
+        Ok(())
+    })?;
+
+    let err = increment(name).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("Invalid Count Error"), "{err}");
+    assert!(replace(name, None).is_err());
+
+    cleanup(first)?;
+    cleanup(second)?;
+    remove(name)
+}
+
+#[test]
+fn replace_test() -> Result<(), DynError> {
+    let name = "relay_test_replace";
+    add(name, 26, 3)?;
+
+    increment(name)?;
+    increment(name)?;
+    let installed = get(name)?.installed;
+
+    let retired = replace(name, Some(5))?;
+    assert_eq!((retired.count, retired.max), (2, 3));
+    assert_eq!(retired.installed, installed);
+    assert!(retired.days().is_some_and(|days| days >= 0.0));
+
+    let relay = get(name)?;
+    assert_eq!((relay.count, relay.max), (0, 5));
+    assert_eq!(relay.installed, Some(retired.retired));
+    assert_eq!(relay.history, vec![retired]);
+
+    // the max carries over unless a new one is given
+    replace(name, None)?;
+    let relay = get(name)?;
+    assert_eq!(relay.max, 5);
+    assert_eq!(relay.history.len(), 2);
+
+    assert!(replace("relay_test_missing", None).is_err());
+
+    remove(name)
+}
+
+/// a profile counting its own relay keeps the history itself
+#[test]
+fn replace_profile_test() -> Result<(), DynError> {
+    let prof = "relay_test_replace_profile";
+    file::init_dirs(prof)?;
+
+    GpioPin::set(String::from("17"), prof)?;
+    RelayCount::set(String::from("99"), prof)?;
+    RelayMax::set(String::from("100"), prof)?;
+
+    let retired = RelayCount::replace(prof, None)?;
+    assert_eq!((retired.count, retired.max), (99, 100));
+    assert_eq!(retired.installed, None, "the first relay has no install date");
+    assert_eq!(retired.days(), None);
+
+    let config = FullConfig::new(prof)?;
+    assert_eq!((config.relay_count, config.relay_max), (0, 100));
+    assert_eq!(config.relay_installed, Some(retired.retired));
+    assert_eq!(config.relay_history, vec![retired]);
+
+    // the comments of the reset keys are kept
+    let raw = fs::read_to_string(format!(
+        "{}/{DIR_NAME}/{prof}/config",
+        env::var("XDG_CONFIG_HOME")?,
+    ))?;
+    assert!(raw.contains("# actuations counted so far, compared against max.\ncount = 0"), "{raw}");
+    assert_eq!(raw.lines().filter(|line| *line == "[[relay.history]]").count(), 1);
+
+    cleanup(prof)
+}
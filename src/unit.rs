@@ -0,0 +1,188 @@
+use crate::{
+    DynError,
+    errors::{
+        InvalidAmountError,
+        MissingFlowRateError,
+    },
+};
+
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+#[cfg(test)]
+mod test;
+
+/// the unit a profile doses in (dosing.unit config entry).
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum VolumeUnit {
+    #[default]
+    Ml,
+    L,
+    FlOz,
+    Gal,
+}
+
+impl VolumeUnit {
+    /// the size of the unit, these match expr::CONSTANTS.
+    pub fn millilitres(self) -> f64 {
+        match self {
+            Self::Ml => 1.0,
+            Self::L => 1000.0,
+            Self::FlOz => 29.5735295625,
+            Self::Gal => 3785.411784,
+        }
+    }
+}
+
+impl FromStr for VolumeUnit {
+    type Err = InvalidAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ml" => Ok(Self::Ml),
+            "l" => Ok(Self::L),
+            "floz" => Ok(Self::FlOz),
+            "gal" => Ok(Self::Gal),
+            _ => Err(InvalidAmountError::new(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for VolumeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ml => "ml",
+            Self::L => "l",
+            Self::FlOz => "floz",
+            Self::Gal => "gal",
+        })
+    }
+}
+
+/// a volume to dispense, ex. 250mL or 8floz.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Amount {
+    pub value: f64,
+    pub unit: VolumeUnit,
+}
+
+impl Amount {
+    /// like FromStr but a bare number is taken to be in unit.
+    pub fn parse_or(s: &str, unit: VolumeUnit) -> Result<Self, InvalidAmountError> {
+        match s.trim().parse::<f64>() {
+            Ok(value) => Self::new(value, unit, s),
+            Err(_) => s.parse::<Self>(),
+        }
+    }
+
+    fn new(value: f64, unit: VolumeUnit, raw: &str) -> Result<Self, InvalidAmountError> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(InvalidAmountError::new(raw.to_string()));
+        }
+
+        Ok(Self { value, unit })
+    }
+
+    /// the amount expressed in another unit.
+    pub fn in_unit(&self, unit: VolumeUnit) -> f64 {
+        self.value * self.unit.millilitres() / unit.millilitres()
+    }
+
+    /// seconds a pump moving flow_rate units per second
+    /// needs to dispense the amount.
+    pub fn seconds(
+        &self,
+        unit: VolumeUnit,
+        flow_rate: Option<f64>,
+    ) -> Result<f64, DynError> {
+        let flow_rate = flow_rate
+            .filter(|rate| rate.is_finite() && *rate > 0.0)
+            .ok_or(MissingFlowRateError::new())?
+        ;
+
+        Ok(self.in_unit(unit) / flow_rate)
+    }
+}
+
+/// a number directly followed by a unit, whitespace between
+/// the two is allowed.
+impl FromStr for Amount {
+    type Err = InvalidAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s
+            .find(|c: char| c.is_alphabetic())
+            .ok_or(InvalidAmountError::new(s.to_string()))?
+        ;
+
+        let (value, unit) = s.split_at(split);
+
+        let value = value
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| InvalidAmountError::new(s.to_string()))?
+        ;
+
+        Self::new(value, unit.trim().parse::<VolumeUnit>()?, s)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
+
+/// what an execution was asked for, either a time which is
+/// scaled by the profiles multiplier or an amount which is
+/// turned into seconds through its flow rate.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Dose {
+    Time(f64),
+    Amount(Amount),
+}
+
+/// a bare number is a time, anything with a unit an amount.
+impl FromStr for Dose {
+    type Err = InvalidAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<f64>() {
+            Ok(time) => Ok(Self::Time(time)),
+            Err(_) => Ok(Self::Amount(s.parse::<Amount>()?)),
+        }
+    }
+}
+
+impl fmt::Display for Dose {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Time(time) => write!(f, "{time}"),
+            Self::Amount(amount) => write!(f, "{amount}"),
+        }
+    }
+}
+
+/// what an actuation was asked for and the seconds it comes to,
+/// requested is the seconds before they were capped to the 
+/// profiles max time.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Request {
+    pub amount: Option<Amount>,
+    pub requested: f64,
+    pub seconds: f64,
+}
+
+impl Request {
+    /// a request for seconds which are already known to be within
+    /// the max time, ex. the rest of an unfinished actuation.
+    pub fn exactly(seconds: f64) -> Self {
+        Self { amount: None, requested: seconds, seconds }
+    }
+
+    pub fn clamped(&self) -> bool {
+        self.seconds < self.requested
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    DynError,
+    expr,
+    unit::*,
+};
+
+#[test]
+fn amount_test() -> Result<(), DynError> {
+    assert_eq!(
+        "250mL".parse::<Amount>()?,
+        Amount { value: 250.0, unit: VolumeUnit::Ml },
+    );
+    assert_eq!(
+        "8 floz".parse::<Amount>()?,
+        Amount { value: 8.0, unit: VolumeUnit::FlOz },
+    );
+    assert_eq!(
+        Amount::parse_or("2", VolumeUnit::Gal)?,
+        Amount { value: 2.0, unit: VolumeUnit::Gal },
+    );
+    assert_eq!(
+        Amount::parse_or("1.5L", VolumeUnit::Gal)?,
+        Amount { value: 1.5, unit: VolumeUnit::L },
+    );
+
+    for raw in ["", "250", "ml", "2 cups", "-1ml", "infml"] {
+        assert!(raw.parse::<Amount>().is_err(), "{raw:?} should not parse");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dose_test() -> Result<(), DynError> {
+    assert_eq!("12.5".parse::<Dose>()?, Dose::Time(12.5));
+    assert_eq!(
+        "8floz".parse::<Dose>()?,
+        Dose::Amount(Amount { value: 8.0, unit: VolumeUnit::FlOz }),
+    );
+
+    // written into systemd services and read back by --exec
+    for raw in ["12.5", "250ml", "0.5gal"] {
+        assert_eq!(raw.parse::<Dose>()?.to_string(), raw);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn seconds_test() -> Result<(), DynError> {
+    let amount = "1l".parse::<Amount>()?;
+
+    assert_eq!(amount.seconds(VolumeUnit::Ml, Some(50.0))?, 20.0);
+    assert_eq!(amount.in_unit(VolumeUnit::L), 1.0);
+    assert!(amount.seconds(VolumeUnit::Ml, None).is_err());
+    assert!(amount.seconds(VolumeUnit::Ml, Some(0.0)).is_err());
+
+    Ok(())
+}
+
+/// the units and expr::CONSTANTS must not drift apart
+#[test]
+fn constants_test() -> Result<(), DynError> {
+    for unit in [VolumeUnit::Ml, VolumeUnit::L, VolumeUnit::FlOz, VolumeUnit::Gal] {
+        assert_eq!(expr::eval(&unit.to_string())?, unit.millilitres());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use crate::{
+    DynError,
+    errors::{
+        InvalidPolicyError,
+        RelayWornOutError,
+    },
+    file,
+    getter_setter::FullConfig,
+};
+
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+#[cfg(test)]
+mod test;
+
+/// percentages of relay.max at which a warning is printed and
+/// logged, unless relay.warn_at says otherwise.
+pub const WARN_AT: [f64; 2] = [80.0, 95.0];
+
+/// what an execution does once the relay has switched as many
+/// times as it is rated for (relay.worn config entry).
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum WearPolicy {
+    /// never actuate, the relay has to be replaced (see
+    /// --replace-relay) first
+    Refuse,
+    /// refuse unless --override-wear is given
+    #[default]
+    Override,
+    /// actuate anyway, with a warning
+    Warn,
+    /// Override after --override-wear was given, this is never
+    /// read from a config
+    Overridden,
+}
+
+impl FromStr for WearPolicy {
+    type Err = InvalidPolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "refuse" => Ok(Self::Refuse),
+            "override" => Ok(Self::Override),
+            "warn" => Ok(Self::Warn),
+            _ => Err(InvalidPolicyError::new()),
+        }
+    }
+}
+
+impl fmt::Display for WearPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Refuse => "refuse",
+            Self::Override => "override",
+            Self::Warn => "warn",
+            Self::Overridden => "overridden",
+        })
+    }
+}
+
+/// percentage of the relays rated actuations left,
+/// 0 once it is worn out.
+pub fn life_remaining(config: &FullConfig) -> f64 {
+    if config.relay_max == 0 {
+        return 0.0;
+    }
+
+    let used = config.relay_count as f64 / config.relay_max as f64;
+    (100.0 * (1.0 - used)).max(0.0)
+}
+
+pub fn worn_out(config: &FullConfig) -> bool {
+    config.relay_count >= config.relay_max
+}
+
+/// the relay.warn_at thresholds passed going from
+/// previous actuations to config.relay_count.
+pub fn crossed(config: &FullConfig, previous: u64) -> Vec<f64> {
+    let used = |count: u64| 100.0 * count as f64 / config.relay_max as f64;
+
+    config.warn_at
+        .iter()
+        .copied()
+        .filter(|threshold| {
+            used(previous) < *threshold && used(config.relay_count) >= *threshold
+        })
+        .collect()
+}
+
+/// called before every actuation, a worn out relay is refused
+/// or warned about according to the profiles wear policy. Both
+/// are logged.
+pub fn check(config: &FullConfig, prof: &str) -> Result<(), DynError> {
+    if !worn_out(config) {
+        return Ok(());
+    }
+
+    match config.wear {
+        WearPolicy::Refuse | WearPolicy::Override => {
+            file::log_worn_out(config, "refused", prof)?;
+
+            Err(RelayWornOutError::new(
+                config.relay_count,
+                config.relay_max,
+                config.wear.to_string(),
+            ).into())
+        }
+
+        WearPolicy::Warn | WearPolicy::Overridden => {
+            println!(
+                "Profile = {prof}: WARNING the relay is worn out ({} of {} actuations)",
+                config.relay_count,
+                config.relay_max,
+            );
+
+            let action = match config.wear {
+                WearPolicy::Overridden => "overridden",
+                _ => "warned",
+            };
+
+            file::log_worn_out(config, action, prof)
+        }
+    }
+}
+
+/// prints and logs a warning for every relay.warn_at threshold
+/// the latest actuation passed.
+pub fn warn(config: &FullConfig, previous: u64, prof: &str) -> Result<(), DynError> {
+    for threshold in crossed(config, previous) {
+        println!(
+            "Profile = {prof}: WARNING the relay has used {threshold}% of its rated actuations ({} of {})",
+            config.relay_count,
+            config.relay_max,
+        );
+
+        file::log_wear(config, threshold, prof)?;
+    }
+
+    Ok(())
+}
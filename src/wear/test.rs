@@ -0,0 +1,115 @@
+use crate::{
+    DynError,
+    fixture::{
+        self,
+        cleanup,
+        execute,
+    },
+    getter_setter::*,
+    gpio::sim::Sim,
+    record::{
+        self,
+        Event,
+    },
+    unit::Dose,
+    wear::*,
+};
+
+fn setup(prof: &str) -> Result<(), DynError> {
+    fixture::setup(prof)?;
+
+    RelayCount::set(String::from("7"), prof)?;
+    RelayMax::set(String::from("10"), prof)?;
+
+    Ok(())
+}
+
+/// the wear and worn out records of the profiles log
+fn log(prof: &str) -> Result<Vec<Event>, DynError> {
+    Ok(record::read(prof)?
+        .records
+        .into_iter()
+        .map(|record| record.event)
+        .filter(|event| matches!(event, Event::Wear { .. } | Event::WornOut { .. }))
+        .collect()
+    )
+}
+
+fn worn_out(action: &str) -> impl Fn(&Event) -> bool + '_ {
+    move |event| matches!(event, Event::WornOut { action: logged, .. } if logged == action)
+}
+
+#[test]
+fn thresholds_test() -> Result<(), DynError> {
+    let prof = "wear_test_thresholds";
+    setup(prof)?;
+
+    // 7 to 8 of 10 passes 80%
+    execute(prof)?;
+
+    assert_eq!(
+        log(prof)?,
+        vec![Event::Wear { percent_used: 80.0, relay_count: 8, relay_max: 10 }],
+    );
+
+    // 8 to 9 passes nothing, 95% is only passed at 10
+    execute(prof)?;
+    assert_eq!(log(prof)?.len(), 1);
+
+    execute(prof)?;
+    assert_eq!(
+        log(prof)?.last(),
+        Some(&Event::Wear { percent_used: 95.0, relay_count: 10, relay_max: 10 }),
+    );
+
+    cleanup(prof)
+}
+
+#[test]
+fn worn_out_test() -> Result<(), DynError> {
+    let prof = "wear_test_worn_out";
+    setup(prof)?;
+    RelayCount::set(String::from("10"), prof)?;
+
+    let err = execute(prof).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err.contains("Relay Worn Out Error"), "{err}");
+    assert_eq!(FullConfig::new(prof)?.relay_count, 10, "a worn out relay actuated");
+    assert!(log(prof)?.iter().any(worn_out("refused")));
+
+    // what --override-wear does
+    let mut config = FullConfig::new(prof)?;
+    config.wear = WearPolicy::Overridden;
+    let mut sim = Sim::new(config.gpio_pin, prof);
+    FullConfig::execute_with(config, Dose::Time(0.001), prof, &mut sim)?;
+
+    assert_eq!(FullConfig::new(prof)?.relay_count, 11);
+    assert!(log(prof)?.iter().any(worn_out("overridden")));
+
+    Wear::set(String::from("warn"), prof)?;
+    execute(prof)?;
+    assert!(log(prof)?.iter().any(worn_out("warned")));
+
+    Wear::set(String::from("refuse"), prof)?;
+    assert!(execute(prof).is_err());
+    assert!(Wear::set(String::from("overridden"), prof).is_err());
+
+    cleanup(prof)
+}
+
+#[test]
+fn life_remaining_test() -> Result<(), DynError> {
+    let prof = "wear_test_life_remaining";
+    setup(prof)?;
+
+    let mut config = FullConfig::new(prof)?;
+    assert!((life_remaining(&config) - 30.0).abs() < 1e-9);
+
+    config.relay_count = 12;
+    assert_eq!(life_remaining(&config), 0.0);
+
+    assert!(WarnAt::set(String::from("80,120"), prof).is_err());
+    WarnAt::set(String::from(""), prof)?;
+    assert!(FullConfig::new(prof)?.warn_at.is_empty());
+
+    cleanup(prof)
+}